@@ -40,12 +40,10 @@ pub fn exec(config: &mut Config, args: &ArgMatches) -> CliResult {
 
     let mut compile_opts = args.compile_options_for_single_package(config, CompileMode::Build)?;
     if !args.is_present("example") && !args.is_present("bin") {
-        if let Some(default_run) = compile_opts.get_package(&ws)?
-            .and_then(|pkg| pkg.manifest().default_run())
-        {
+        if let Some(default_run) = ops::default_run(&ws, &compile_opts)? {
             compile_opts.filter = CompileFilter::new(
                 false,
-                vec![default_run.to_owned()],
+                vec![default_run],
                 false,
                 vec![],
                 false,
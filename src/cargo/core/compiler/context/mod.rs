@@ -536,12 +536,7 @@ impl<'a> Links<'a> {
                 lib
             )
         }
-        if !unit.pkg
-            .manifest()
-            .targets()
-            .iter()
-            .any(|t| t.is_custom_build())
-        {
+        if !unit.pkg.manifest().has_custom_build() {
             bail!(
                 "package `{}` specifies that it links to `{}` but does not \
                  have a custom build script",
@@ -301,24 +301,20 @@ fn maybe_lib<'a>(
 /// of work is still returned. `None` is only returned if the package has no
 /// build script.
 fn dep_build_script<'a>(unit: &Unit<'a>, bcx: &BuildContext) -> Option<(Unit<'a>, ProfileFor)> {
-    unit.pkg
-        .targets()
-        .iter()
-        .find(|t| t.is_custom_build())
-        .map(|t| {
-            // The profile stored in the Unit is the profile for the thing
-            // the custom build script is running for.
-            (
-                Unit {
-                    pkg: unit.pkg,
-                    target: t,
-                    profile: bcx.profiles.get_profile_run_custom_build(&unit.profile),
-                    kind: unit.kind,
-                    mode: CompileMode::RunCustomBuild,
-                },
-                ProfileFor::CustomBuild,
-            )
-        })
+    unit.pkg.manifest().custom_build_target().map(|t| {
+        // The profile stored in the Unit is the profile for the thing
+        // the custom build script is running for.
+        (
+            Unit {
+                pkg: unit.pkg,
+                target: t,
+                profile: bcx.profiles.get_profile_run_custom_build(&unit.profile),
+                kind: unit.kind,
+                mode: CompileMode::RunCustomBuild,
+            },
+            ProfileFor::CustomBuild,
+        )
+    })
 }
 
 /// Choose the correct mode for dependencies.
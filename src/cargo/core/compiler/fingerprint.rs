@@ -633,7 +633,7 @@ fn local_fingerprints_deps(
 fn write_fingerprint(loc: &Path, fingerprint: &Fingerprint) -> CargoResult<()> {
     let hash = fingerprint.hash();
     debug!("write fingerprint: {}", loc.display());
-    paths::write(loc, util::to_hex(hash).as_bytes())?;
+    paths::write(loc, util::u64_to_hex(hash).as_bytes())?;
     paths::write(
         &loc.with_extension("json"),
         &serde_json::to_vec(&fingerprint).unwrap(),
@@ -662,7 +662,7 @@ fn compare_old_fingerprint(loc: &Path, new_fingerprint: &Fingerprint) -> CargoRe
     let old_fingerprint_short = paths::read(loc)?;
     let new_hash = new_fingerprint.hash();
 
-    if util::to_hex(new_hash) == old_fingerprint_short {
+    if util::u64_to_hex(new_hash) == old_fingerprint_short {
         return Ok(());
     }
 
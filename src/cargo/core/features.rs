@@ -55,6 +55,8 @@ use failure::Error;
 use util::errors::CargoResult;
 
 /// The edition of the compiler (RFC 2052)
+///
+/// Editions are ordered by year, so variants must be declared oldest first.
 #[derive(Clone, Copy, Debug, Hash, PartialOrd, Ord, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Edition {
     /// The 2015 edition
@@ -189,6 +191,12 @@ features! {
 
         // "default-run" manifest option,
         [unstable] default_run: bool,
+
+        // "rust-version" manifest option
+        [unstable] rust_version: bool,
+
+        // Inheriting package metadata from `[workspace.package]`
+        [unstable] workspace_inheritance: bool,
     }
 }
 
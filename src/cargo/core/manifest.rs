@@ -1,27 +1,79 @@
-use std::collections::{BTreeMap, HashMap};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
-use semver::Version;
+use glob::Pattern;
+use semver::{Version, VersionReq};
+use serde::de::DeserializeOwned;
 use serde::ser;
+use serde_json;
 use toml;
 use url::Url;
 
 use core::interning::InternedString;
-use core::profiles::Profiles;
-use core::{Dependency, PackageId, PackageIdSpec, SourceId, Summary};
+use core::compiler::CompileMode;
+use core::dependency::{Kind, Platform};
+use core::profiles::{Profile, ProfileFor, Profiles};
+use core::{Dependency, FeatureMap, PackageId, PackageIdSpec, SourceId, Summary};
 use core::{Edition, Feature, Features, WorkspaceConfig};
 use util::errors::*;
+use util::paths;
 use util::toml::TomlManifest;
-use util::Config;
+use util::{lev_distance, Config, Sha256, SpdxExpr};
 
 pub enum EitherManifest {
     Real(Manifest),
     Virtual(VirtualManifest),
 }
 
+impl EitherManifest {
+    pub fn as_real(&self) -> Option<&Manifest> {
+        match *self {
+            EitherManifest::Real(ref m) => Some(m),
+            EitherManifest::Virtual(..) => None,
+        }
+    }
+    pub fn as_virtual(&self) -> Option<&VirtualManifest> {
+        match *self {
+            EitherManifest::Real(..) => None,
+            EitherManifest::Virtual(ref vm) => Some(vm),
+        }
+    }
+    pub fn workspace_config(&self) -> &WorkspaceConfig {
+        match *self {
+            EitherManifest::Real(ref m) => m.workspace_config(),
+            EitherManifest::Virtual(ref vm) => vm.workspace_config(),
+        }
+    }
+    pub fn profiles(&self) -> &Profiles {
+        match *self {
+            EitherManifest::Real(ref m) => m.profiles(),
+            EitherManifest::Virtual(ref vm) => vm.profiles(),
+        }
+    }
+    pub fn patch(&self) -> &HashMap<Url, Vec<Dependency>> {
+        match *self {
+            EitherManifest::Real(ref m) => m.patch(),
+            EitherManifest::Virtual(ref vm) => vm.patch(),
+        }
+    }
+    pub fn replace(&self) -> &[(PackageIdSpec, Dependency)] {
+        match *self {
+            EitherManifest::Real(ref m) => m.replace(),
+            EitherManifest::Virtual(ref vm) => vm.replace(),
+        }
+    }
+    pub fn warnings(&self) -> &Warnings {
+        match *self {
+            EitherManifest::Real(ref m) => m.warnings(),
+            EitherManifest::Virtual(ref vm) => vm.warnings(),
+        }
+    }
+}
+
 /// Contains all the information about a package, as loaded from a Cargo.toml.
 #[derive(Clone, Debug)]
 pub struct Manifest {
@@ -31,6 +83,7 @@ pub struct Manifest {
     warnings: Warnings,
     exclude: Vec<String>,
     include: Vec<String>,
+    package_matcher: PackageMatcher,
     metadata: ManifestMetadata,
     custom_metadata: Option<toml::Value>,
     profiles: Profiles,
@@ -42,8 +95,13 @@ pub struct Manifest {
     original: Rc<TomlManifest>,
     features: Features,
     edition: Edition,
+    autobins: bool,
+    autoexamples: bool,
+    autotests: bool,
+    autobenches: bool,
     im_a_teapot: Option<bool>,
-    default_run: Option<String>,
+    default_run: Option<DefaultRun>,
+    rust_version: Option<VersionReq>,
 }
 
 /// When parsing `Cargo.toml`, some warnings should silenced
@@ -58,6 +116,17 @@ pub struct DelayedWarning {
 #[derive(Clone, Debug)]
 pub struct Warnings(Vec<DelayedWarning>);
 
+/// The binary picked by `cargo run` when neither `--bin` nor `--example` is
+/// passed, as configured by the `default-run` manifest key.
+#[derive(Clone, Debug)]
+pub enum DefaultRun {
+    /// `default-run = "foo"`
+    Bin(String),
+    /// `default-run = [{ bin = "foo", required-features = [..] }, ..]`, where
+    /// the first binary whose required features are all enabled is picked.
+    Candidates(Vec<(String, RequiredFeatures)>),
+}
+
 #[derive(Clone, Debug)]
 pub struct VirtualManifest {
     replace: Vec<(PackageIdSpec, Dependency)>,
@@ -67,6 +136,45 @@ pub struct VirtualManifest {
     warnings: Warnings,
 }
 
+/// The compiled form of a manifest's `include` and `exclude` lists, matching
+/// paths with the glob-like rules `cargo package` uses to pick files.
+#[derive(Clone, Debug)]
+pub struct PackageMatcher {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl PackageMatcher {
+    pub fn new(include: &[String], exclude: &[String]) -> CargoResult<PackageMatcher> {
+        fn compile(patterns: &[String]) -> CargoResult<Vec<Pattern>> {
+            patterns
+                .iter()
+                .map(|p| {
+                    let pattern: &str = if p.starts_with('/') { &p[1..] } else { p };
+                    Pattern::new(pattern)
+                        .map_err(|e| format_err!("could not parse glob pattern `{}`: {}", p, e))
+                })
+                .collect()
+        }
+        Ok(PackageMatcher {
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+        })
+    }
+
+    /// Whether the file at `rel_path`, relative to the package root, goes
+    /// into the package. `include` and `exclude` are mutually exclusive, a
+    /// non-empty `include` list takes precedence.
+    pub fn should_package(&self, rel_path: &Path) -> bool {
+        let matches = |patterns: &[Pattern]| patterns.iter().any(|p| p.matches_path(rel_path));
+        if self.include.is_empty() {
+            !matches(&self.exclude[..])
+        } else {
+            matches(&self.include[..])
+        }
+    }
+}
+
 /// General metadata about a package which is just blindly uploaded to the
 /// registry.
 ///
@@ -91,12 +199,209 @@ pub struct ManifestMetadata {
     pub links: Option<String>,
 }
 
+impl ManifestMetadata {
+    /// Checks that `homepage`, `repository` and `documentation` are valid
+    /// URLs, returning a description of each one which isn't.
+    ///
+    /// This is only advisory, it's still up to the registry to reject them.
+    pub fn validate_urls(&self) -> Vec<String> {
+        let fields = [
+            ("homepage", &self.homepage),
+            ("repository", &self.repository),
+            ("documentation", &self.documentation),
+        ];
+        fields
+            .iter()
+            .filter_map(|&(name, value)| {
+                let value = value.as_ref()?;
+                Url::parse(value)
+                    .err()
+                    .map(|e| format!("{} is not a valid URL: {}", name, e))
+            })
+            .collect()
+    }
+
+    /// shields.io image URLs for the `[badges]` of providers we know how to
+    /// render, along with the provider's name.
+    ///
+    /// Badges from other providers, or missing the keys their URL needs,
+    /// are skipped.
+    pub fn badge_urls(&self) -> Vec<(String, Url)> {
+        self.badges
+            .iter()
+            .filter_map(|(provider, badge)| {
+                let with_branch = |url: String| match badge.get("branch") {
+                    Some(branch) => format!("{}/{}.svg", url, branch),
+                    None => format!("{}.svg", url),
+                };
+                let url = match &provider[..] {
+                    "travis-ci" => with_branch(format!(
+                        "https://img.shields.io/travis/{}",
+                        badge.get("repository")?
+                    )),
+                    "appveyor" => with_branch(format!(
+                        "https://img.shields.io/appveyor/ci/{}",
+                        badge.get("repository")?
+                    )),
+                    "codecov" => with_branch(format!(
+                        "https://img.shields.io/codecov/c/{}/{}",
+                        badge.get("service").map(|s| &s[..]).unwrap_or("github"),
+                        badge.get("repository")?
+                    )),
+                    "maintenance" => {
+                        let status = badge.get("status")?;
+                        let color = match &status[..] {
+                            "actively-developed" => "brightgreen",
+                            "passively-maintained" => "yellowgreen",
+                            "as-is" => "yellow",
+                            "experimental" => "blue",
+                            "looking-for-maintainer" => "orange",
+                            "deprecated" => "red",
+                            _ => return None,
+                        };
+                        // Dashes separate the parts of a static badge, so
+                        // literal ones are doubled.
+                        format!(
+                            "https://img.shields.io/badge/maintenance-{}-{}.svg",
+                            status.replace("-", "--"),
+                            color
+                        )
+                    }
+                    _ => return None,
+                };
+                Some((provider.clone(), Url::parse(&url).ok()?))
+            })
+            .collect()
+    }
+
+    /// Checks `keywords` and `categories` against the rules crates.io
+    /// enforces, so that publishing can fail before anything is uploaded.
+    ///
+    /// Whether each category is one crates.io knows about isn't checked.
+    pub fn validate_for_publish(&self) -> CargoResult<()> {
+        const MAX_KEYWORDS: usize = 5;
+        const MAX_KEYWORD_LEN: usize = 20;
+
+        if self.keywords.len() > MAX_KEYWORDS {
+            bail!(
+                "expected at most {} keywords per crate, found {}",
+                MAX_KEYWORDS,
+                self.keywords.len()
+            )
+        }
+        for keyword in self.keywords.iter() {
+            if keyword.is_empty() {
+                bail!("keywords cannot be empty strings")
+            }
+            if keyword.len() > MAX_KEYWORD_LEN {
+                bail!(
+                    "keyword `{}` is longer than {} characters",
+                    keyword,
+                    MAX_KEYWORD_LEN
+                )
+            }
+            let mut chars = keyword.chars();
+            let valid = chars.next().map_or(false, |c| c.is_ascii_alphanumeric())
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '+');
+            if !valid {
+                bail!(
+                    "keyword `{}` must start with a letter or digit and only \
+                     contain letters, digits, `_`, `-` or `+`",
+                    keyword
+                )
+            }
+        }
+        if self.categories.iter().any(|c| c.is_empty()) {
+            bail!("categories cannot be empty strings")
+        }
+        Ok(())
+    }
+
+    /// The fields crates.io expects which aren't set, or are set to an empty
+    /// string, suitable for warning about before publishing.
+    ///
+    /// `license-file` counts in place of `license`.
+    pub fn missing_publish_fields(&self) -> Vec<&'static str> {
+        fn missing(field: &Option<String>) -> bool {
+            field.as_ref().map_or(true, |s| s.is_empty())
+        }
+
+        let mut ret = Vec::new();
+        if missing(&self.description) {
+            ret.push("description");
+        }
+        if missing(&self.license) && missing(&self.license_file) {
+            ret.push("license or license-file");
+        }
+        if missing(&self.repository) {
+            ret.push("repository");
+        }
+        ret
+    }
+
+    /// Parses `license` as an SPDX license expression, returning `None` if
+    /// no license is set.
+    pub fn parse_license(&self) -> CargoResult<Option<SpdxExpr>> {
+        match self.license {
+            Some(ref license) => Ok(Some(license.parse()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the `readme` file, relative to `package_root`, returning `None`
+    /// if no readme is set.
+    pub fn read_readme(&self, package_root: &Path) -> CargoResult<Option<String>> {
+        let readme = match self.readme {
+            Some(ref readme) => readme,
+            None => return Ok(None),
+        };
+        let contents = paths::read(&package_root.join(readme))
+            .chain_err(|| format!("failed to read the readme `{}`", readme))?;
+        Ok(Some(contents))
+    }
+
+    /// Returns a copy of this metadata with any field left unset filled in
+    /// from `workspace`, the `[workspace.package]` table of the workspace
+    /// root.
+    ///
+    /// Values set explicitly by the package always win. `license` and
+    /// `license-file` are inherited together, and only if the package sets
+    /// neither of them. `description`, `readme`, `badges` and `links` are
+    /// never inherited.
+    pub fn inherit(&self, workspace: &ManifestMetadata) -> ManifestMetadata {
+        fn vec_or(child: &[String], parent: &[String]) -> Vec<String> {
+            if child.is_empty() { parent } else { child }.to_vec()
+        }
+        fn opt_or(child: &Option<String>, parent: &Option<String>) -> Option<String> {
+            child.as_ref().or(parent.as_ref()).cloned()
+        }
+
+        let mut ret = self.clone();
+        ret.authors = vec_or(&self.authors, &workspace.authors);
+        ret.keywords = vec_or(&self.keywords, &workspace.keywords);
+        ret.categories = vec_or(&self.categories, &workspace.categories);
+        if self.license.is_none() && self.license_file.is_none() {
+            ret.license = workspace.license.clone();
+            ret.license_file = workspace.license_file.clone();
+        }
+        ret.homepage = opt_or(&self.homepage, &workspace.homepage);
+        ret.repository = opt_or(&self.repository, &workspace.repository);
+        ret.documentation = opt_or(&self.documentation, &workspace.documentation);
+        ret
+    }
+}
+
+/// Crate types without a `LibKind` variant of their own which nonetheless
+/// produce an artifact foreign code can link against.
+const FOREIGN_LINKABLE_OTHER_KINDS: &[&str] = &["cdylib"];
+
 #[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum LibKind {
     Lib,
     Rlib,
     Dylib,
     ProcMacro,
+    StaticLib,
     Other(String),
 }
 
@@ -107,6 +412,7 @@ impl LibKind {
             "rlib" => LibKind::Rlib,
             "dylib" => LibKind::Dylib,
             "proc-macro" => LibKind::ProcMacro,
+            "staticlib" => LibKind::StaticLib,
             s => LibKind::Other(s.to_string()),
         }
     }
@@ -118,14 +424,31 @@ impl LibKind {
             LibKind::Rlib => "rlib",
             LibKind::Dylib => "dylib",
             LibKind::ProcMacro => "proc-macro",
+            LibKind::StaticLib => "staticlib",
             LibKind::Other(ref s) => s,
         }
     }
 
+    /// Whether other Rust crates can link against this kind of output.
+    ///
+    /// A `staticlib` bundles its dependencies for consumption by a foreign
+    /// linker, so rustc can't use it as an `--extern` dependency.
     pub fn linkable(&self) -> bool {
         match *self {
             LibKind::Lib | LibKind::Rlib | LibKind::Dylib | LibKind::ProcMacro => true,
-            LibKind::Other(..) => false,
+            LibKind::StaticLib | LibKind::Other(..) => false,
+        }
+    }
+
+    /// Whether foreign code, through a C linker, can link against this kind
+    /// of output. Of the crate types cargo doesn't otherwise know about,
+    /// only those listed in `FOREIGN_LINKABLE_OTHER_KINDS` (currently just
+    /// `cdylib`) are.
+    pub fn is_foreign_linkable(&self) -> bool {
+        match *self {
+            LibKind::StaticLib | LibKind::Dylib => true,
+            LibKind::Lib | LibKind::Rlib | LibKind::ProcMacro => false,
+            LibKind::Other(ref s) => FOREIGN_LINKABLE_OTHER_KINDS.contains(&&s[..]),
         }
     }
 }
@@ -196,6 +519,29 @@ pub struct Target {
     doctest: bool,
     harness: bool, // whether to use the test harness (--test)
     for_host: bool,
+    // whether rustdoc should scrape this target for examples, `None` falls
+    // back to `is_doc_scrape_eligible`
+    doc_scrape: Option<bool>,
+    edition: Edition,
+}
+
+/// The features a target requires, all of which must be enabled for the
+/// target to be built.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequiredFeatures(Vec<InternedString>);
+
+impl RequiredFeatures {
+    pub fn new(features: &[String]) -> RequiredFeatures {
+        RequiredFeatures(features.iter().map(|f| InternedString::new(f)).collect())
+    }
+
+    pub fn features(&self) -> &[InternedString] {
+        &self.0
+    }
+
+    pub fn is_satisfied_by(&self, enabled: &HashSet<InternedString>) -> bool {
+        self.0.iter().all(|f| enabled.contains(f))
+    }
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -275,6 +621,8 @@ compact_debug! {
                 doctest
                 harness
                 for_host
+                doc_scrape
+                edition
             )]
         }
     }
@@ -297,16 +645,23 @@ impl Manifest {
         workspace: WorkspaceConfig,
         features: Features,
         edition: Edition,
+        autobins: bool,
+        autoexamples: bool,
+        autotests: bool,
+        autobenches: bool,
         im_a_teapot: Option<bool>,
-        default_run: Option<String>,
+        default_run: Option<DefaultRun>,
+        rust_version: Option<VersionReq>,
         original: Rc<TomlManifest>,
-    ) -> Manifest {
-        Manifest {
+    ) -> CargoResult<Manifest> {
+        let package_matcher = PackageMatcher::new(&include, &exclude)?;
+        Ok(Manifest {
             summary,
             targets,
             warnings: Warnings::new(),
             exclude,
             include,
+            package_matcher,
             links,
             metadata,
             custom_metadata,
@@ -317,22 +672,52 @@ impl Manifest {
             workspace,
             features,
             edition,
+            autobins,
+            autoexamples,
+            autotests,
+            autobenches,
             original,
             im_a_teapot,
             default_run,
+            rust_version,
             publish_lockfile,
-        }
+        })
     }
 
     pub fn dependencies(&self) -> &[Dependency] {
         self.summary.dependencies()
     }
+    /// The `[dependencies]` of this package, including target-specific ones.
+    pub fn normal_dependencies(&self) -> impl Iterator<Item = &Dependency> {
+        self.dependencies_of_kind(Kind::Normal)
+    }
+    /// The `[dev-dependencies]` of this package, including target-specific ones.
+    pub fn dev_dependencies(&self) -> impl Iterator<Item = &Dependency> {
+        self.dependencies_of_kind(Kind::Development)
+    }
+    /// The `[build-dependencies]` of this package, including target-specific ones.
+    pub fn build_dependencies(&self) -> impl Iterator<Item = &Dependency> {
+        self.dependencies_of_kind(Kind::Build)
+    }
+    fn dependencies_of_kind(&self, kind: Kind) -> impl Iterator<Item = &Dependency> {
+        self.dependencies().iter().filter(move |d| d.kind() == kind)
+    }
     pub fn exclude(&self) -> &[String] {
         &self.exclude
     }
     pub fn include(&self) -> &[String] {
         &self.include
     }
+
+    /// The `include`/`exclude` lists compiled when the manifest was loaded.
+    pub fn package_matcher(&self) -> &PackageMatcher {
+        &self.package_matcher
+    }
+    /// Whether `rel_path`, relative to the package root, is left out of the
+    /// package by the `include`/`exclude` lists, see `PackageMatcher`.
+    pub fn is_path_excluded(&self, rel_path: &Path) -> bool {
+        !self.package_matcher.should_package(rel_path)
+    }
     pub fn metadata(&self) -> &ManifestMetadata {
         &self.metadata
     }
@@ -345,9 +730,72 @@ impl Manifest {
     pub fn summary(&self) -> &Summary {
         &self.summary
     }
+    /// The `[features]` table, with each feature's values resolved to
+    /// other features, dependencies or dependency features.
+    pub fn declared_features(&self) -> &FeatureMap {
+        self.summary.features()
+    }
     pub fn targets(&self) -> &[Target] {
         &self.targets
     }
+    /// The targets whose kind satisfies `predicate`.
+    pub fn targets_of_kind<'a, F>(&'a self, predicate: F) -> impl Iterator<Item = &'a Target> + 'a
+    where
+        F: Fn(&TargetKind) -> bool + 'a,
+    {
+        self.targets.iter().filter(move |t| predicate(t.kind()))
+    }
+    pub fn bin_targets(&self) -> impl Iterator<Item = &Target> {
+        self.targets_of_kind(|k| *k == TargetKind::Bin)
+    }
+    /// The library target, a package has at most one.
+    pub fn lib_target(&self) -> Option<&Target> {
+        self.targets.iter().find(|t| t.is_lib())
+    }
+    pub fn example_targets(&self) -> impl Iterator<Item = &Target> {
+        self.targets_of_kind(|k| match *k {
+            TargetKind::ExampleBin | TargetKind::ExampleLib(..) => true,
+            _ => false,
+        })
+    }
+    pub fn test_targets(&self) -> impl Iterator<Item = &Target> {
+        self.targets_of_kind(|k| *k == TargetKind::Test)
+    }
+    pub fn bench_targets(&self) -> impl Iterator<Item = &Target> {
+        self.targets_of_kind(|k| *k == TargetKind::Bench)
+    }
+    /// The targets whose required features, if any, are all in `enabled`.
+    pub fn enabled_targets(&self, enabled: &HashSet<InternedString>) -> Vec<&Target> {
+        self.targets
+            .iter()
+            .filter(|t| match t.required_features_expr() {
+                Some(required) => required.is_satisfied_by(enabled),
+                None => true,
+            })
+            .collect()
+    }
+    /// Checks that there's at most one library target and that no two
+    /// targets of the same kind share a name, which manifests parsed from a
+    /// `Cargo.toml` already guarantee but synthesized ones may not.
+    pub fn validate_target_uniqueness(&self) -> CargoResult<()> {
+        check_unique_targets(&self.name(), &self.targets)
+    }
+
+    /// Names shared by more than one target of the same kind, along with
+    /// the kinds of those targets. Targets of different kinds, such as a
+    /// binary and an example, may share a name.
+    pub fn duplicate_target_names(&self) -> Vec<(String, Vec<&TargetKind>)> {
+        find_duplicate_target_names(&self.targets)
+    }
+
+    /// Whether this package has a build script.
+    pub fn has_custom_build(&self) -> bool {
+        self.custom_build_target().is_some()
+    }
+    /// The build script target, if this package has one.
+    pub fn custom_build_target(&self) -> Option<&Target> {
+        self.targets.iter().find(|t| t.is_custom_build())
+    }
     pub fn version(&self) -> &Version {
         self.package_id().version()
     }
@@ -357,24 +805,166 @@ impl Manifest {
     pub fn warnings(&self) -> &Warnings {
         &self.warnings
     }
+    /// Returns this manifest back if it has no warnings, otherwise an error
+    /// listing all of them, critical or not.
+    pub fn into_strict(self) -> CargoResult<Manifest> {
+        if self.warnings.warnings().is_empty() {
+            return Ok(self);
+        }
+        let warnings = self.warnings.warnings();
+        let mut msg = format!(
+            "manifest for `{}` has {} warning(s), which are denied:",
+            self.name(),
+            warnings.len()
+        );
+        for warning in warnings {
+            msg.push_str("\n  ");
+            msg.push_str(&warning.message);
+        }
+        Err(format_err!("{}", msg))
+    }
     pub fn profiles(&self) -> &Profiles {
         &self.profiles
     }
+    /// The profile `target` of package `pkg_id` is built with in `mode`
+    /// according to this manifest's `[profile]` tables, including any
+    /// overrides for `pkg_id`.
+    pub fn target_profile(
+        &self,
+        pkg_id: &PackageId,
+        is_member: bool,
+        target: &Target,
+        mode: CompileMode,
+        release: bool,
+    ) -> Profile {
+        let profile_for = if target.is_custom_build() {
+            ProfileFor::CustomBuild
+        } else {
+            ProfileFor::Any
+        };
+        self.profiles.get_profile(pkg_id, is_member, profile_for, mode, release)
+    }
     pub fn publish(&self) -> &Option<Vec<String>> {
         &self.publish
     }
+    /// Whether this package may be published to the registry named
+    /// `registry`, which is the case unless `publish` lists other registries
+    /// or is `false`.
+    pub fn can_publish_to(&self, registry: &str) -> bool {
+        match self.publish {
+            Some(ref registries) => registries.iter().any(|r| r == registry),
+            None => true,
+        }
+    }
+    /// Whether `publish = false`, i.e. this package may never be published.
+    pub fn publish_forbidden(&self) -> bool {
+        match self.publish {
+            Some(ref registries) => registries.is_empty(),
+            None => false,
+        }
+    }
     pub fn publish_lockfile(&self) -> bool {
         self.publish_lockfile
     }
     pub fn replace(&self) -> &[(PackageIdSpec, Dependency)] {
         &self.replace
     }
+    /// The `[replace]` dependency for `id`, from the first spec matching it.
+    pub fn replacement_for(&self, id: &PackageId) -> Option<&Dependency> {
+        self.replace
+            .iter()
+            .find(|&&(ref spec, _)| spec.matches(id))
+            .map(|&(_, ref dep)| dep)
+    }
     pub fn original(&self) -> &TomlManifest {
         &self.original
     }
+    /// The original manifest serialized back to TOML.
+    ///
+    /// This is deterministic: tables are emitted in a fixed order with their
+    /// keys sorted, so the same manifest always yields the same bytes
+    /// regardless of how its `Cargo.toml` was laid out.
+    pub fn canonical_bytes(&self) -> CargoResult<Vec<u8>> {
+        let toml = toml::to_string(&*self.original)?;
+        Ok(toml.into_bytes())
+    }
     pub fn patch(&self) -> &HashMap<Url, Vec<Dependency>> {
         &self.patch
     }
+    /// The `[patch]` entries which apply to `source`.
+    pub fn patches_for(&self, source: &SourceId) -> &[Dependency] {
+        self.patch
+            .get(source.url())
+            .map(|deps| &deps[..])
+            .unwrap_or(&[])
+    }
+
+    /// How the `[replace]` and `[patch]` sections of `other` differ from
+    /// this manifest's, with `[replace]` entries keyed by their package spec
+    /// and `[patch]` entries by the URL of the source they patch.
+    pub fn dependency_override_diff(&self, other: &Manifest) -> OverrideDiff {
+        fn replace(m: &Manifest) -> BTreeMap<String, &Dependency> {
+            m.replace
+                .iter()
+                .map(|&(ref spec, ref dep)| (spec.to_string(), dep))
+                .collect()
+        }
+        fn patch(m: &Manifest) -> BTreeMap<String, &Vec<Dependency>> {
+            m.patch
+                .iter()
+                .map(|(url, deps)| (url.to_string(), deps))
+                .collect()
+        }
+        OverrideDiff {
+            replace: EntryDiff::between(replace(self), replace(other)),
+            patch: EntryDiff::between(patch(self), patch(other)),
+        }
+    }
+
+    /// A SHA-256 hash of everything in this manifest which affects dependency
+    /// resolution, to tell whether a previously computed resolve is stale.
+    ///
+    /// Metadata, targets and warnings are left out, and so is the location
+    /// of the package: path dependencies are hashed by their path relative
+    /// to `package_root`, so manifests which resolve identically have the
+    /// same fingerprint wherever they are.
+    pub fn resolution_fingerprint(&self, package_root: &Path) -> CargoResult<[u8; 32]> {
+        let mut replace = self.replace
+            .iter()
+            .map(|&(ref spec, ref dep)| {
+                (spec.to_string(), ResolutionDependency::new(dep, package_root))
+            })
+            .collect::<Vec<_>>();
+        replace.sort_by(|a, b| a.0.cmp(&b.0));
+        let name = self.name();
+        let inputs = ResolutionInputs {
+            name: &name,
+            version: self.version().to_string(),
+            links: self.links(),
+            namespaced_features: self.summary.namespaced_features(),
+            dependencies: self.dependencies()
+                .iter()
+                .map(|dep| ResolutionDependency::new(dep, package_root))
+                .collect(),
+            features: self.summary.features(),
+            edition: self.edition,
+            replace,
+            patch: self.patch
+                .iter()
+                .map(|(url, deps)| {
+                    let deps = deps.iter()
+                        .map(|dep| ResolutionDependency::new(dep, package_root))
+                        .collect();
+                    (url.as_str(), deps)
+                })
+                .collect(),
+        };
+        let encoded = serde_json::to_vec(&inputs)?;
+        let mut hasher = Sha256::new();
+        hasher.update(&encoded);
+        Ok(hasher.finish())
+    }
+
     pub fn links(&self) -> Option<&str> {
         self.links.as_ref().map(|s| &s[..])
     }
@@ -383,6 +973,33 @@ impl Manifest {
         &self.workspace
     }
 
+    /// The `workspace.members` list, or `None` if this isn't a workspace root
+    /// or doesn't list its members.
+    pub fn workspace_members(&self) -> Option<&[String]> {
+        match self.workspace {
+            WorkspaceConfig::Root(ref root) => root.members(),
+            WorkspaceConfig::Member { .. } => None,
+        }
+    }
+
+    /// The `workspace.default-members` list, or `None` if this isn't a
+    /// workspace root or doesn't list default members.
+    pub fn workspace_default_members(&self) -> Option<&[String]> {
+        match self.workspace {
+            WorkspaceConfig::Root(ref root) => root.default_members(),
+            WorkspaceConfig::Member { .. } => None,
+        }
+    }
+
+    /// The `workspace.default-members` list joined onto `workspace_root`.
+    ///
+    /// Entries are joined as written, any globs in them are left for
+    /// `Workspace` to expand.
+    pub fn default_member_paths(&self, workspace_root: &Path) -> Option<Vec<PathBuf>> {
+        self.workspace_default_members()
+            .map(|members| members.iter().map(|m| workspace_root.join(m)).collect())
+    }
+
     pub fn features(&self) -> &Features {
         &self.features
     }
@@ -391,6 +1008,12 @@ impl Manifest {
         self.summary = summary;
     }
 
+    /// Fills in the metadata this package leaves unset from its workspace's
+    /// `[workspace.package]` table, see `ManifestMetadata::inherit`.
+    pub fn inherit_metadata(&mut self, workspace: &ManifestMetadata) {
+        self.metadata = self.metadata.inherit(workspace);
+    }
+
     pub fn map_source(self, to_replace: &SourceId, replace_with: &SourceId) -> Manifest {
         Manifest {
             summary: self.summary.map_source(to_replace, replace_with),
@@ -399,33 +1022,60 @@ impl Manifest {
     }
 
     pub fn feature_gate(&self) -> CargoResult<()> {
-        if self.im_a_teapot.is_some() {
-            self.features
-                .require(Feature::test_dummy_unstable())
-                .chain_err(|| {
-                    format_err!(
-                        "the `im-a-teapot` manifest key is unstable and may \
-                         not work properly in England"
-                    )
-                })?;
-        }
-
-        if self.default_run.is_some() {
-            self.features
-                .require(Feature::default_run())
-                .chain_err(|| {
-                    format_err!(
-                        "the `default-run` manifest key is unstable"
-                    )
-                })?;
+        // Each unstable manifest key along with the feature enabling it.
+        let gates: &[(bool, &'static Feature, &'static str)] = &[
+            (
+                self.im_a_teapot.is_some(),
+                Feature::test_dummy_unstable(),
+                "the `im-a-teapot` manifest key is unstable and may \
+                 not work properly in England",
+            ),
+            (
+                self.default_run.is_some(),
+                Feature::default_run(),
+                "the `default-run` manifest key is unstable",
+            ),
+            (
+                self.rust_version.is_some(),
+                Feature::rust_version(),
+                "the `rust-version` manifest key is unstable",
+            ),
+        ];
+
+        let mut errors = gates
+            .iter()
+            .filter(|&&(used, _, _)| used)
+            .filter_map(|&(_, feature, msg)| {
+                self.features.require(feature).chain_err(|| msg).err()
+            })
+            .map(CargoError::from)
+            .collect::<Vec<_>>();
+
+        if errors.len() <= 1 {
+            return match errors.pop() {
+                Some(e) => Err(e),
+                None => Ok(()),
+            };
         }
+        let mut msg = "multiple unstable manifest keys are used".to_string();
+        for error in errors {
+            msg.push_str("\n");
+            for cause in error.causes() {
+                msg.push_str(&format!("\n{}", cause));
+            }
+        }
+        bail!("{}", msg)
+    }
 
-        Ok(())
+    /// The unstable `im-a-teapot` key, which only exists to test feature
+    /// gates.
+    pub fn im_a_teapot(&self) -> Option<bool> {
+        self.im_a_teapot
     }
 
     // Just a helper function to test out `-Z` flags on Cargo
     pub fn print_teapot(&self, config: &Config) {
-        if let Some(teapot) = self.im_a_teapot {
+        if let Some(teapot) = self.im_a_teapot() {
             if config.cli_unstable().print_im_a_teapot {
                 println!("im-a-teapot = {}", teapot);
             }
@@ -436,12 +1086,325 @@ impl Manifest {
         self.edition
     }
 
+    /// Whether this package's edition is `edition` or a later one.
+    pub fn edition_at_least(&self, edition: Edition) -> bool {
+        self.edition >= edition
+    }
+
     pub fn custom_metadata(&self) -> Option<&toml::Value> {
         self.custom_metadata.as_ref()
     }
 
+    /// Deserializes the `[package.metadata.<key>]` table, if present.
+    pub fn custom_metadata_section<T: DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> CargoResult<Option<T>> {
+        let section = match self.custom_metadata.as_ref().and_then(|m| m.get(key)) {
+            Some(section) => section.clone(),
+            None => return Ok(None),
+        };
+        let section = section
+            .try_into()
+            .chain_err(|| format!("failed to parse `package.metadata.{}`", key))?;
+        Ok(Some(section))
+    }
+
+    /// The binary named by a plain `default-run = "foo"`. A list of
+    /// candidates depends on the enabled features, see
+    /// `default_runnable_target`.
     pub fn default_run(&self) -> Option<&str> {
-        self.default_run.as_ref().map(|s| &s[..])
+        match self.default_run {
+            Some(DefaultRun::Bin(ref bin)) => Some(&bin[..]),
+            Some(DefaultRun::Candidates(_)) | None => None,
+        }
+    }
+
+    /// The `default-run` key as written, in either of its forms.
+    pub fn default_run_config(&self) -> Option<&DefaultRun> {
+        self.default_run.as_ref()
+    }
+
+    /// The binary `cargo run` runs when neither `--bin` nor `--example` is
+    /// passed, given the features enabled on this package.
+    ///
+    /// With `default-run` set to a list, the first candidate whose own and
+    /// whose binary's `required-features` are all enabled is picked. Without
+    /// a pick the package must have exactly one binary, whether its required
+    /// features are enabled is left for compilation to report.
+    pub fn default_runnable_target(
+        &self,
+        enabled: &HashSet<InternedString>,
+    ) -> CargoResult<&Target> {
+        let runnable = |bin: &str| {
+            self.bin_targets()
+                .find(|t| t.name() == bin)
+                .and_then(|t| t.required_features_expr())
+                .map_or(true, |features| features.is_satisfied_by(enabled))
+        };
+        let name = match self.default_run {
+            Some(DefaultRun::Bin(ref bin)) => Some(&bin[..]),
+            Some(DefaultRun::Candidates(ref candidates)) => candidates
+                .iter()
+                .find(|&&(ref bin, ref features)| {
+                    features.is_satisfied_by(enabled) && runnable(bin)
+                })
+                .map(|&(ref bin, _)| &bin[..]),
+            None => None,
+        };
+        if let Some(name) = name {
+            if let Some(target) = self.bin_targets().find(|t| t.name() == name) {
+                return Ok(target);
+            }
+            let suggestion = self.bin_targets()
+                .map(|t| (lev_distance(name, t.name()), t))
+                .filter(|&(d, _)| d < 4)
+                .min_by_key(|t| t.0)
+                .map(|t| t.1);
+            match suggestion {
+                Some(s) => bail!(
+                    "no bin target named `{}`\n\nDid you mean `{}`?",
+                    name,
+                    s.name()
+                ),
+                None => bail!("no bin target named `{}`", name),
+            }
+        }
+        let bins = self.bin_targets().collect::<Vec<_>>();
+        match bins.len() {
+            0 => bail!("a bin target must be available for `cargo run`"),
+            1 => Ok(bins[0]),
+            _ => {
+                let mut names = bins.iter().map(|t| t.name()).collect::<Vec<_>>();
+                names.sort();
+                bail!(
+                    "`cargo run` requires that a project only have one \
+                     executable; use the `--bin` option to specify which one \
+                     to run\navailable binaries: {}",
+                    names.join(", ")
+                )
+            }
+        }
+    }
+
+    /// The minimum version of rustc this package declares it supports.
+    pub fn rust_version(&self) -> Option<&VersionReq> {
+        self.rust_version.as_ref()
+    }
+
+    /// Whether binaries found in `src/bin` are built. With no `[[bin]]`
+    /// sections they always are, otherwise it's up to `autobins`, which is on
+    /// by default starting with the 2018 edition.
+    pub fn autobins(&self) -> bool {
+        self.autobins
+    }
+
+    /// Like `autobins`, for examples found in `examples`.
+    pub fn autoexamples(&self) -> bool {
+        self.autoexamples
+    }
+
+    /// Like `autobins`, for tests found in `tests`.
+    pub fn autotests(&self) -> bool {
+        self.autotests
+    }
+
+    /// Like `autobins`, for benchmarks found in `benches`.
+    pub fn autobenches(&self) -> bool {
+        self.autobenches
+    }
+}
+
+/// The changes to `[replace]` and `[patch]` between two manifests, see
+/// `Manifest::dependency_override_diff`.
+#[derive(Debug, Default, PartialEq)]
+pub struct OverrideDiff {
+    pub replace: EntryDiff,
+    pub patch: EntryDiff,
+}
+
+/// The keys of the entries added, removed and changed in one section.
+#[derive(Debug, Default, PartialEq)]
+pub struct EntryDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl OverrideDiff {
+    pub fn is_empty(&self) -> bool {
+        self.replace.is_empty() && self.patch.is_empty()
+    }
+}
+
+impl EntryDiff {
+    fn between<V: PartialEq>(old: BTreeMap<String, V>, new: BTreeMap<String, V>) -> EntryDiff {
+        let mut diff = EntryDiff::default();
+        for (key, value) in old.iter() {
+            match new.get(key) {
+                Some(new_value) if new_value != value => diff.changed.push(key.clone()),
+                Some(_) => {}
+                None => diff.removed.push(key.clone()),
+            }
+        }
+        diff.added = new.into_iter()
+            .map(|(key, _)| key)
+            .filter(|key| !old.contains_key(key))
+            .collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// What `Manifest::resolution_fingerprint` hashes, with maps in a fixed
+/// order so that the encoding is canonical.
+#[derive(Serialize)]
+struct ResolutionInputs<'a> {
+    name: &'a str,
+    version: String,
+    links: Option<&'a str>,
+    namespaced_features: bool,
+    dependencies: Vec<ResolutionDependency<'a>>,
+    features: &'a FeatureMap,
+    edition: Edition,
+    replace: Vec<(String, ResolutionDependency<'a>)>,
+    patch: BTreeMap<&'a str, Vec<ResolutionDependency<'a>>>,
+}
+
+/// A dependency as `Manifest::resolution_fingerprint` hashes it, which is
+/// how `Dependency` serializes except for its source.
+#[derive(Serialize)]
+struct ResolutionDependency<'a> {
+    name: &'a str,
+    source: String,
+    req: String,
+    kind: Kind,
+    rename: Option<&'a str>,
+    optional: bool,
+    uses_default_features: bool,
+    features: Vec<&'a str>,
+    target: Option<&'a Platform>,
+}
+
+impl<'a> ResolutionDependency<'a> {
+    fn new(dep: &'a Dependency, package_root: &Path) -> ResolutionDependency<'a> {
+        let source_id = dep.source_id();
+        let source = match source_id.url().to_file_path() {
+            Ok(ref path) if source_id.is_path() => relative_path(path, package_root),
+            _ => source_id.to_url().to_string(),
+        };
+        ResolutionDependency {
+            name: dep.name().as_str(),
+            source,
+            req: dep.version_req().to_string(),
+            kind: dep.kind(),
+            rename: dep.rename(),
+            optional: dep.is_optional(),
+            uses_default_features: dep.uses_default_features(),
+            features: dep.features().iter().map(|f| &**f).collect(),
+            target: dep.platform(),
+        }
+    }
+}
+
+/// `path` relative to `base`, joined with `/` on every platform.
+fn relative_path(path: &Path, base: &Path) -> String {
+    let path = path.components().collect::<Vec<_>>();
+    let base = base.components().collect::<Vec<_>>();
+    let common = path.iter().zip(&base).take_while(|&(a, b)| a == b).count();
+    base[common..]
+        .iter()
+        .map(|_| "..".to_string())
+        .chain(
+            path[common..]
+                .iter()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned()),
+        )
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// See `Manifest::validate_target_uniqueness`.
+fn check_unique_targets(package: &str, targets: &[Target]) -> CargoResult<()> {
+    let libs = targets.iter().filter(|t| t.is_lib()).count();
+    if libs > 1 {
+        bail!(
+            "package `{}` has {} library targets, but only one is allowed",
+            package,
+            libs
+        )
+    }
+    let mut seen = HashSet::new();
+    for target in targets.iter() {
+        let class = target_kind_class(target.kind());
+        if !seen.insert((class, target.name())) {
+            bail!(
+                "found duplicate {target_kind} name {name}, \
+                 but all {target_kind} targets must have a unique name",
+                target_kind = class,
+                name = target.name()
+            )
+        }
+    }
+    Ok(())
+}
+
+/// See `Manifest::duplicate_target_names`.
+fn find_duplicate_target_names(targets: &[Target]) -> Vec<(String, Vec<&TargetKind>)> {
+    let mut by_name = BTreeMap::new();
+    for target in targets.iter() {
+        by_name
+            .entry((target.name(), target_kind_class(target.kind())))
+            .or_insert_with(Vec::new)
+            .push(target.kind());
+    }
+    by_name
+        .into_iter()
+        .filter(|&(_, ref kinds)| kinds.len() > 1)
+        .map(|((name, _), kinds)| (name.to_string(), kinds))
+        .collect()
+}
+
+/// Checks that no two of `manifests` declare the same `links` value, as a
+/// native library can only be linked once.
+pub fn check_links_collisions(manifests: &[&Manifest]) -> CargoResult<()> {
+    let mut links = BTreeMap::new();
+    for manifest in manifests {
+        if let Some(lib) = manifest.links() {
+            links
+                .entry(lib)
+                .or_insert_with(Vec::new)
+                .push(manifest.package_id());
+        }
+    }
+    for (lib, ids) in links {
+        if ids.len() > 1 {
+            let ids = ids.iter()
+                .map(|id| format!("`{}`", id))
+                .collect::<Vec<_>>();
+            bail!(
+                "multiple packages link to native library `{}`, \
+                 but a native library can be linked only once: {}",
+                lib,
+                ids.join(", ")
+            )
+        }
+    }
+    Ok(())
+}
+
+/// The group of target kinds within which target names must be unique.
+fn target_kind_class(kind: &TargetKind) -> &'static str {
+    match *kind {
+        TargetKind::Lib(..) => "lib",
+        TargetKind::Bin => "bin",
+        TargetKind::Test => "test",
+        TargetKind::Bench => "bench",
+        TargetKind::ExampleBin | TargetKind::ExampleLib(..) => "example",
+        TargetKind::CustomBuild => "build script",
     }
 }
 
@@ -504,17 +1467,16 @@ impl Target {
             for_host: false,
             tested: true,
             benched: true,
+            doc_scrape: None,
+            edition: Edition::Edition2015,
         }
     }
 
     pub fn lib_target(name: &str, crate_targets: Vec<LibKind>, src_path: PathBuf) -> Target {
-        Target {
-            kind: TargetKind::Lib(crate_targets),
-            name: name.to_string(),
-            doctest: true,
-            doc: true,
-            ..Target::with_path(src_path)
-        }
+        TargetBuilder::new(TargetKind::Lib(crate_targets), name, src_path)
+            .doctest(true)
+            .doc(true)
+            .build()
     }
 
     pub fn bin_target(
@@ -522,25 +1484,19 @@ impl Target {
         src_path: PathBuf,
         required_features: Option<Vec<String>>,
     ) -> Target {
-        Target {
-            kind: TargetKind::Bin,
-            name: name.to_string(),
-            required_features,
-            doc: true,
-            ..Target::with_path(src_path)
-        }
+        TargetBuilder::new(TargetKind::Bin, name, src_path)
+            .required_features(required_features)
+            .doc(true)
+            .build()
     }
 
     /// Builds a `Target` corresponding to the `build = "build.rs"` entry.
     pub fn custom_build_target(name: &str, src_path: PathBuf) -> Target {
-        Target {
-            kind: TargetKind::CustomBuild,
-            name: name.to_string(),
-            for_host: true,
-            benched: false,
-            tested: false,
-            ..Target::with_path(src_path)
-        }
+        TargetBuilder::new(TargetKind::CustomBuild, name, src_path)
+            .for_host(true)
+            .benched(false)
+            .tested(false)
+            .build()
     }
 
     pub fn example_target(
@@ -548,6 +1504,18 @@ impl Target {
         crate_targets: Vec<LibKind>,
         src_path: PathBuf,
         required_features: Option<Vec<String>>,
+    ) -> Target {
+        Target::example_target_with_harness(name, crate_targets, src_path, required_features, true)
+    }
+
+    /// Like `example_target`, but for an example which may provide its own
+    /// `main` with `harness = false`.
+    pub fn example_target_with_harness(
+        name: &str,
+        crate_targets: Vec<LibKind>,
+        src_path: PathBuf,
+        required_features: Option<Vec<String>>,
+        harness: bool,
     ) -> Target {
         let kind = if crate_targets.is_empty() {
             TargetKind::ExampleBin
@@ -555,14 +1523,12 @@ impl Target {
             TargetKind::ExampleLib(crate_targets)
         };
 
-        Target {
-            kind,
-            name: name.to_string(),
-            required_features,
-            tested: false,
-            benched: false,
-            ..Target::with_path(src_path)
-        }
+        TargetBuilder::new(kind, name, src_path)
+            .required_features(required_features)
+            .tested(false)
+            .benched(false)
+            .harness(harness)
+            .build()
     }
 
     pub fn test_target(
@@ -570,13 +1536,10 @@ impl Target {
         src_path: PathBuf,
         required_features: Option<Vec<String>>,
     ) -> Target {
-        Target {
-            kind: TargetKind::Test,
-            name: name.to_string(),
-            required_features,
-            benched: false,
-            ..Target::with_path(src_path)
-        }
+        TargetBuilder::new(TargetKind::Test, name, src_path)
+            .required_features(required_features)
+            .benched(false)
+            .build()
     }
 
     pub fn bench_target(
@@ -584,36 +1547,109 @@ impl Target {
         src_path: PathBuf,
         required_features: Option<Vec<String>>,
     ) -> Target {
+        TargetBuilder::new(TargetKind::Bench, name, src_path)
+            .required_features(required_features)
+            .tested(false)
+            .build()
+    }
+
+    /// A copy of this target which is built from `src_path` instead.
+    pub fn with_src_path(&self, src_path: PathBuf) -> Target {
+        assert!(
+            src_path.is_absolute(),
+            "`{}` is not absolute",
+            src_path.display()
+        );
         Target {
-            kind: TargetKind::Bench,
-            name: name.to_string(),
-            required_features,
-            tested: false,
-            ..Target::with_path(src_path)
+            src_path: NonHashedPathBuf { path: src_path },
+            ..self.clone()
         }
     }
 
     pub fn name(&self) -> &str {
         &self.name
     }
+    /// Whether `other` is logically the same target as `self`, that is it
+    /// has the same kind and name regardless of where its source lives.
+    ///
+    /// Note that `==` also compares `src_path`, which `Hash` skips. That is
+    /// consistent, since targets which are `==` still always hash equally,
+    /// but it means `==` can't be used to dedup targets across checkouts.
+    pub fn same_kind_and_name(&self, other: &Target) -> bool {
+        self.kind == other.kind && self.name == other.name
+    }
     pub fn crate_name(&self) -> String {
         self.name.replace("-", "_")
     }
+    /// Like `crate_name`, but fails if the result isn't a valid identifier:
+    /// it must be non-empty, can't start with a digit and may only contain
+    /// alphanumeric characters and underscores.
+    pub fn sanitized_crate_name(&self) -> CargoResult<String> {
+        let name = self.crate_name();
+        match name.chars().next() {
+            None => bail!("the name of {} is empty", self.description_named()),
+            Some(c) if c.is_digit(10) => bail!(
+                "the name of {} starts with a digit, so it cannot be used \
+                 as a crate name",
+                self.description_named()
+            ),
+            Some(_) => {}
+        }
+        if let Some(c) = name.chars().find(|&c| !c.is_alphanumeric() && c != '_') {
+            bail!(
+                "invalid character `{}` in the name of {}, which cannot be \
+                 used as a crate name",
+                c,
+                self.description_named()
+            )
+        }
+        Ok(name)
+    }
     pub fn src_path(&self) -> &Path {
         &self.src_path.path
     }
+    /// The source path relative to `package_root`, or `None` if the source
+    /// lives outside of it.
+    pub fn relative_src_path(&self, package_root: &Path) -> Option<PathBuf> {
+        self.src_path
+            .path
+            .strip_prefix(package_root)
+            .ok()
+            .map(|p| p.to_path_buf())
+    }
     pub fn required_features(&self) -> Option<&Vec<String>> {
         self.required_features.as_ref()
     }
+    pub fn required_features_expr(&self) -> Option<RequiredFeatures> {
+        self.required_features
+            .as_ref()
+            .map(|features| RequiredFeatures::new(features))
+    }
     pub fn kind(&self) -> &TargetKind {
         &self.kind
     }
+    /// The crate types of a library or library example, `None` otherwise.
+    pub fn lib_kinds(&self) -> Option<&[LibKind]> {
+        match self.kind {
+            TargetKind::Lib(ref kinds) | TargetKind::ExampleLib(ref kinds) => Some(&kinds[..]),
+            _ => None,
+        }
+    }
     pub fn tested(&self) -> bool {
         self.tested
     }
     pub fn harness(&self) -> bool {
         self.harness
     }
+    /// Whether `cargo test` runs this target under the libtest harness.
+    pub fn runs_under_test_harness(&self) -> bool {
+        self.tested() && self.harness()
+    }
+    /// Whether `cargo test` runs this target as a plain executable, as it
+    /// provides its own `main` with `harness = false`.
+    pub fn runs_as_custom_test(&self) -> bool {
+        self.tested() && !self.harness()
+    }
     pub fn documented(&self) -> bool {
         self.doc
     }
@@ -623,6 +1659,27 @@ impl Target {
     pub fn benched(&self) -> bool {
         self.benched
     }
+    /// The edition of the package this target belongs to.
+    pub fn edition(&self) -> Edition {
+        self.edition
+    }
+
+    /// Whether rustdoc can scrape this target for usage examples, which is
+    /// the case for documented examples.
+    pub fn is_doc_scrape_eligible(&self) -> bool {
+        self.is_example() && self.documented()
+    }
+
+    /// Whether rustdoc should scrape this target for usage examples.
+    pub fn doc_scrape(&self) -> bool {
+        self.doc_scrape.unwrap_or_else(|| self.is_doc_scrape_eligible())
+    }
+
+    /// The `doctest` setting as given, whereas `doctested` also accounts for
+    /// whether this kind of target can have doctests at all.
+    pub fn doctest_flag(&self) -> bool {
+        self.doctest
+    }
 
     pub fn doctested(&self) -> bool {
         self.doctest && match self.kind {
@@ -662,6 +1719,13 @@ impl Target {
         })
     }
 
+    pub fn is_proc_macro(&self) -> bool {
+        match self.kind {
+            TargetKind::Lib(ref libs) => libs.iter().any(|l| *l == LibKind::ProcMacro),
+            _ => false,
+        }
+    }
+
     pub fn linkable(&self) -> bool {
         match self.kind {
             TargetKind::Lib(ref kinds) => kinds.iter().any(|k| k.linkable()),
@@ -669,6 +1733,15 @@ impl Target {
         }
     }
 
+    /// Whether this is a library with an output foreign code can link
+    /// against, see `LibKind::is_foreign_linkable`.
+    pub fn is_foreign_linkable(&self) -> bool {
+        match self.kind {
+            TargetKind::Lib(ref kinds) => kinds.iter().any(|k| k.is_foreign_linkable()),
+            _ => false,
+        }
+    }
+
     pub fn is_bin(&self) -> bool {
         self.kind == TargetKind::Bin
     }
@@ -688,6 +1761,11 @@ impl Target {
         }
     }
 
+    /// Whether this target can be executed with `cargo run`.
+    pub fn is_runnable(&self) -> bool {
+        self.is_bin() || self.is_bin_example()
+    }
+
     pub fn is_test(&self) -> bool {
         self.kind == TargetKind::Test
     }
@@ -698,6 +1776,100 @@ impl Target {
         self.kind == TargetKind::CustomBuild
     }
 
+    /// Whether this target is built by default when compiling a package in
+    /// `mode`, e.g. benchmarks for `cargo bench` or documented targets for
+    /// `cargo doc`.
+    pub fn builds_in_mode(&self, mode: CompileMode) -> bool {
+        match mode {
+            CompileMode::Bench => self.benched(),
+            CompileMode::Test => self.tested() || self.is_example(),
+            CompileMode::Build | CompileMode::Check { .. } => self.is_bin() || self.is_lib(),
+            CompileMode::Doc { .. } => self.documented(),
+            CompileMode::Doctest => self.doctested(),
+            CompileMode::RunCustomBuild => self.is_custom_build(),
+        }
+    }
+
+    /// A SHA-256 hash of everything this target is compiled from, given the
+    /// contents of its source file as `src_bytes`.
+    ///
+    /// Like `Hash`, this leaves out the absolute `src_path`, so identical
+    /// targets over identical sources hash the same wherever they live.
+    pub fn content_hash(&self, src_bytes: &[u8]) -> [u8; 32] {
+        // Every variable length field is prefixed with its length, so no two
+        // different targets encode to the same bytes.
+        fn bytes(hasher: &mut Sha256, bytes: &[u8]) {
+            hasher.update(format!("{}:", bytes.len()).as_bytes());
+            hasher.update(bytes);
+        }
+        fn flag(hasher: &mut Sha256, flag: Option<bool>) {
+            hasher.update(match flag {
+                None => b"-",
+                Some(false) => b"0",
+                Some(true) => b"1",
+            });
+        }
+
+        let mut hasher = Sha256::new();
+        let kind = match self.kind {
+            TargetKind::Lib(..) => "lib",
+            TargetKind::Bin => "bin",
+            TargetKind::Test => "test",
+            TargetKind::Bench => "bench",
+            TargetKind::ExampleLib(..) => "example-lib",
+            TargetKind::ExampleBin => "example-bin",
+            TargetKind::CustomBuild => "custom-build",
+        };
+        bytes(&mut hasher, kind.as_bytes());
+        bytes(&mut hasher, self.name.as_bytes());
+        let crate_types = self.rustc_crate_types();
+        bytes(&mut hasher, crate_types.len().to_string().as_bytes());
+        for crate_type in crate_types {
+            bytes(&mut hasher, crate_type.as_bytes());
+        }
+        match self.required_features {
+            Some(ref features) => {
+                bytes(&mut hasher, features.len().to_string().as_bytes());
+                for feature in features {
+                    bytes(&mut hasher, feature.as_bytes());
+                }
+            }
+            None => bytes(&mut hasher, b"-"),
+        }
+        flag(&mut hasher, Some(self.tested));
+        flag(&mut hasher, Some(self.benched));
+        flag(&mut hasher, Some(self.doc));
+        flag(&mut hasher, Some(self.doctest));
+        flag(&mut hasher, Some(self.harness));
+        flag(&mut hasher, Some(self.for_host));
+        flag(&mut hasher, self.doc_scrape);
+        bytes(&mut hasher, self.edition.to_string().as_bytes());
+        bytes(&mut hasher, src_bytes);
+        hasher.finish()
+    }
+
+    /// Orders targets by kind and then name, which unlike the absolute
+    /// `src_path` is the same on every machine, so sorting with this gives a
+    /// reproducible order.
+    pub fn cmp_stable(&self, other: &Target) -> Ordering {
+        (&self.kind, &self.name).cmp(&(&other.kind, &other.name))
+    }
+
+    /// Describes this target for humans, e.g. `binary "foo"` or `library`,
+    /// unlike `Display` which is meant for debugging.
+    pub fn description_named(&self) -> String {
+        match self.kind {
+            TargetKind::Lib(..) => "library".to_string(),
+            TargetKind::Bin => format!("binary \"{}\"", self.name),
+            TargetKind::Test => format!("test \"{}\"", self.name),
+            TargetKind::Bench => format!("bench \"{}\"", self.name),
+            TargetKind::ExampleBin | TargetKind::ExampleLib(..) => {
+                format!("example \"{}\"", self.name)
+            }
+            TargetKind::CustomBuild => "build script".to_string(),
+        }
+    }
+
     /// Returns the arguments suitable for `--crate-type` to pass to rustc.
     pub fn rustc_crate_types(&self) -> Vec<&str> {
         match self.kind {
@@ -712,6 +1884,35 @@ impl Target {
         }
     }
 
+    /// The value for a single `--crate-type` flag covering all of this
+    /// library's crate types, e.g. `rlib,cdylib`, or `None` for targets
+    /// which are built with rustc's default crate type.
+    pub fn crate_type_arg(&self) -> Option<String> {
+        match self.kind {
+            TargetKind::Lib(..) | TargetKind::ExampleLib(..) => {
+                Some(self.rustc_crate_types().join(","))
+            }
+            TargetKind::CustomBuild
+            | TargetKind::Bench
+            | TargetKind::Test
+            | TargetKind::ExampleBin
+            | TargetKind::Bin => None,
+        }
+    }
+
+    /// Like `rustc_crate_types`, but with `lib` expanded to the `rlib` rustc
+    /// builds for it by default, and without duplicates.
+    pub fn effective_crate_types(&self) -> Vec<&str> {
+        let mut ret = Vec::new();
+        for crate_type in self.rustc_crate_types() {
+            let crate_type = if crate_type == "lib" { "rlib" } else { crate_type };
+            if !ret.contains(&crate_type) {
+                ret.push(crate_type);
+            }
+        }
+        ret
+    }
+
     pub fn can_lto(&self) -> bool {
         match self.kind {
             TargetKind::Lib(ref v) => {
@@ -726,6 +1927,13 @@ impl Target {
         self.tested = tested;
         self
     }
+    pub fn set_required_features(
+        &mut self,
+        required_features: Option<Vec<String>>,
+    ) -> &mut Target {
+        self.required_features = required_features;
+        self
+    }
     pub fn set_benched(&mut self, benched: bool) -> &mut Target {
         self.benched = benched;
         self
@@ -746,6 +1954,140 @@ impl Target {
         self.doc = doc;
         self
     }
+    pub fn set_doc_scrape(&mut self, doc_scrape: bool) -> &mut Target {
+        self.doc_scrape = Some(doc_scrape);
+        self
+    }
+    pub fn set_edition(&mut self, edition: Edition) -> &mut Target {
+        self.edition = edition;
+        self
+    }
+
+    /// Changes the kind of this target, resetting the flags which depend on
+    /// the kind (whether it's tested, benched, documented, ...) to the
+    /// defaults for the new kind. The name, source path and required
+    /// features are kept.
+    ///
+    /// Binaries, examples, tests and benchmarks can be changed into one
+    /// another. Libraries and build scripts are built too differently, so
+    /// they can't be changed into anything else nor anything into them. A
+    /// library example needs at least one crate type.
+    pub fn set_kind(&mut self, kind: TargetKind) -> CargoResult<()> {
+        let convertible = |kind: &TargetKind| match *kind {
+            TargetKind::Lib(..) | TargetKind::CustomBuild => false,
+            _ => true,
+        };
+        if !convertible(&self.kind) || !convertible(&kind) {
+            bail!(
+                "cannot change {} into a {} target, only binaries, examples, \
+                 tests and benchmarks can be changed into one another",
+                self.description_named(),
+                target_kind_class(&kind)
+            )
+        }
+        if let TargetKind::ExampleLib(ref kinds) = kind {
+            if kinds.is_empty() {
+                bail!(
+                    "cannot change {} into a library example without any crate types",
+                    self.description_named()
+                )
+            }
+        }
+        let src_path = self.src_path.path.clone();
+        let defaults = match kind {
+            TargetKind::Bin => Target::bin_target(&self.name, src_path, None),
+            TargetKind::Test => Target::test_target(&self.name, src_path, None),
+            TargetKind::Bench => Target::bench_target(&self.name, src_path, None),
+            TargetKind::ExampleBin => Target::example_target(&self.name, vec![], src_path, None),
+            TargetKind::ExampleLib(kinds) => {
+                Target::example_target(&self.name, kinds, src_path, None)
+            }
+            TargetKind::Lib(..) | TargetKind::CustomBuild => unreachable!(),
+        };
+        *self = Target {
+            required_features: self.required_features.take(),
+            doc_scrape: self.doc_scrape,
+            edition: self.edition,
+            ..defaults
+        };
+        Ok(())
+    }
+
+    /// Adds `kind` to the crate types of this library or example library,
+    /// unless it's already one of them.
+    pub fn push_lib_kind(&mut self, kind: LibKind) -> CargoResult<()> {
+        match self.kind {
+            TargetKind::Lib(ref mut kinds) | TargetKind::ExampleLib(ref mut kinds) => {
+                if !kinds.contains(&kind) {
+                    kinds.push(kind);
+                }
+                return Ok(());
+            }
+            _ => {}
+        }
+        bail!(
+            "cannot add crate type `{}` to {}, which is not a library",
+            kind.crate_type(),
+            self.description_named()
+        )
+    }
+}
+
+/// A builder for `Target`s of any kind.
+///
+/// Fields which aren't set explicitly keep the same defaults as every other
+/// target constructor.
+#[derive(Clone, Debug)]
+pub struct TargetBuilder {
+    target: Target,
+}
+
+impl TargetBuilder {
+    pub fn new(kind: TargetKind, name: &str, src_path: PathBuf) -> TargetBuilder {
+        TargetBuilder {
+            target: Target {
+                kind,
+                name: name.to_string(),
+                ..Target::with_path(src_path)
+            },
+        }
+    }
+
+    pub fn required_features(
+        &mut self,
+        required_features: Option<Vec<String>>,
+    ) -> &mut TargetBuilder {
+        self.target.required_features = required_features;
+        self
+    }
+    pub fn tested(&mut self, tested: bool) -> &mut TargetBuilder {
+        self.target.tested = tested;
+        self
+    }
+    pub fn benched(&mut self, benched: bool) -> &mut TargetBuilder {
+        self.target.benched = benched;
+        self
+    }
+    pub fn doc(&mut self, doc: bool) -> &mut TargetBuilder {
+        self.target.doc = doc;
+        self
+    }
+    pub fn doctest(&mut self, doctest: bool) -> &mut TargetBuilder {
+        self.target.doctest = doctest;
+        self
+    }
+    pub fn harness(&mut self, harness: bool) -> &mut TargetBuilder {
+        self.target.harness = harness;
+        self
+    }
+    pub fn for_host(&mut self, for_host: bool) -> &mut TargetBuilder {
+        self.target.for_host = for_host;
+        self
+    }
+
+    pub fn build(&self) -> Target {
+        self.target.clone()
+    }
 }
 
 impl fmt::Display for Target {
@@ -785,4 +2127,838 @@ impl Warnings {
     pub fn warnings(&self) -> &[DelayedWarning] {
         &self.0
     }
+
+    pub fn critical<'a>(&'a self) -> impl Iterator<Item = &'a DelayedWarning> + 'a {
+        self.0.iter().filter(|w| w.is_critical)
+    }
+
+    pub fn has_critical(&self) -> bool {
+        self.critical().next().is_some()
+    }
+
+    /// Appends all of `other`'s warnings after the ones already collected.
+    pub fn extend(&mut self, other: Warnings) {
+        self.0.extend(other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+    use std::collections::{BTreeMap, HashSet};
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    use tempfile;
+
+    use core::compiler::CompileMode;
+    use core::interning::InternedString;
+    use core::Edition;
+    use util::hash_u64;
+    use super::{check_unique_targets, find_duplicate_target_names, LibKind, ManifestMetadata,
+                PackageMatcher, Target, TargetBuilder, TargetKind, Warnings};
+
+    fn src(path: &str) -> PathBuf {
+        env::current_dir().unwrap().join(path)
+    }
+
+    fn metadata() -> ManifestMetadata {
+        ManifestMetadata {
+            authors: Vec::new(),
+            keywords: Vec::new(),
+            categories: Vec::new(),
+            license: None,
+            license_file: None,
+            description: None,
+            readme: None,
+            homepage: None,
+            repository: None,
+            documentation: None,
+            badges: Default::default(),
+            links: None,
+        }
+    }
+
+    #[test]
+    fn required_features_expr() {
+        let features = vec!["a".to_string(), "b".to_string()];
+        let target = Target::bin_target("foo", src("src/main.rs"), Some(features));
+        let expr = target.required_features_expr().unwrap();
+        assert_eq!(
+            expr.features(),
+            &[InternedString::new("a"), InternedString::new("b")]
+        );
+
+        let mut enabled = HashSet::new();
+        enabled.insert(InternedString::new("a"));
+        assert!(!expr.is_satisfied_by(&enabled));
+        enabled.insert(InternedString::new("b"));
+        assert!(expr.is_satisfied_by(&enabled));
+
+        let target = Target::bin_target("foo", src("src/main.rs"), None);
+        assert!(target.required_features_expr().is_none());
+    }
+
+    #[test]
+    fn runnable_targets() {
+        let lib = Target::lib_target("foo", vec![LibKind::Lib], src("src/lib.rs"));
+        let bin = Target::bin_target("foo", src("src/main.rs"), None);
+        let test = Target::test_target("t", src("tests/t.rs"), None);
+        let bench = Target::bench_target("b", src("benches/b.rs"), None);
+        let example_bin = Target::example_target("e", vec![], src("examples/e.rs"), None);
+        let example_lib = Target::example_target(
+            "e",
+            vec![LibKind::Dylib],
+            src("examples/e.rs"),
+            None,
+        );
+        let build = Target::custom_build_target("build-script-build", src("build.rs"));
+
+        assert!(!lib.is_runnable());
+        assert!(bin.is_runnable());
+        assert!(!test.is_runnable());
+        assert!(!bench.is_runnable());
+        assert!(example_bin.is_runnable());
+        assert!(!example_lib.is_runnable());
+        assert!(!build.is_runnable());
+    }
+
+    #[test]
+    fn staticlib_kind() {
+        let kind = LibKind::from_str("staticlib");
+        assert_eq!(kind, LibKind::StaticLib);
+        assert_eq!(kind.crate_type(), "staticlib");
+        assert!(!kind.linkable());
+
+        let lib = Target::lib_target("foo", vec![LibKind::StaticLib], src("src/lib.rs"));
+        assert!(!lib.linkable());
+        assert!(lib.can_lto());
+        assert!(!lib.is_cdylib());
+    }
+
+    #[test]
+    fn package_matcher_rejects_invalid_patterns() {
+        let err = PackageMatcher::new(&[], &["src/[".to_string()]).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("could not parse glob pattern `src/[`")
+        );
+    }
+
+    #[test]
+    fn critical_warnings() {
+        let mut warnings = Warnings::new();
+        warnings.add_warning("a".to_string());
+        assert!(!warnings.has_critical());
+
+        let mut other = Warnings::new();
+        other.add_critical_warning("b".to_string());
+        other.add_warning("c".to_string());
+        warnings.extend(other);
+
+        assert!(warnings.has_critical());
+        let critical = warnings.critical().map(|w| &w.message[..]).collect::<Vec<_>>();
+        assert_eq!(critical, ["b"]);
+        let all = warnings
+            .warnings()
+            .iter()
+            .map(|w| &w.message[..])
+            .collect::<Vec<_>>();
+        assert_eq!(all, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn target_builder() {
+        let target = TargetBuilder::new(TargetKind::Test, "t", src("tests/t.rs"))
+            .harness(false)
+            .for_host(true)
+            .required_features(Some(vec!["a".to_string()]))
+            .build();
+        assert_eq!(target.name(), "t");
+        assert!(target.is_test());
+        assert!(!target.harness());
+        assert!(target.for_host());
+        assert!(target.tested());
+        assert!(target.benched());
+        assert!(!target.documented());
+        assert_eq!(target.required_features(), Some(&vec!["a".to_string()]));
+
+        assert_eq!(
+            Target::bench_target("b", src("benches/b.rs"), None),
+            TargetBuilder::new(TargetKind::Bench, "b", src("benches/b.rs"))
+                .tested(false)
+                .build()
+        );
+    }
+
+    #[test]
+    fn lib_kinds() {
+        let kinds = vec![LibKind::from_str("rlib"), LibKind::from_str("cdylib")];
+        let lib = Target::lib_target("foo", kinds.clone(), src("src/lib.rs"));
+        assert_eq!(lib.lib_kinds(), Some(&kinds[..]));
+
+        let example = Target::example_target("e", kinds.clone(), src("examples/e.rs"), None);
+        assert_eq!(example.lib_kinds(), Some(&kinds[..]));
+
+        let bin = Target::bin_target("foo", src("src/main.rs"), None);
+        assert_eq!(bin.lib_kinds(), None);
+    }
+
+    #[test]
+    fn doc_scrape() {
+        let mut lib = Target::lib_target("foo", vec![LibKind::Lib], src("src/lib.rs"));
+        let mut bin = Target::bin_target("foo", src("src/main.rs"), None);
+        let mut test = Target::test_target("t", src("tests/t.rs"), None);
+        let mut bench = Target::bench_target("b", src("benches/b.rs"), None);
+        let mut build = Target::custom_build_target("build-script-build", src("build.rs"));
+        for target in vec![&mut lib, &mut bin, &mut test, &mut bench, &mut build] {
+            target.set_doc(true);
+            assert!(!target.is_doc_scrape_eligible(), "{:?}", target);
+            assert!(!target.doc_scrape(), "{:?}", target);
+        }
+
+        let mut example_bin = Target::example_target("e", vec![], src("examples/e.rs"), None);
+        let mut example_lib = Target::example_target(
+            "e",
+            vec![LibKind::Dylib],
+            src("examples/e.rs"),
+            None,
+        );
+        for target in vec![&mut example_bin, &mut example_lib] {
+            assert!(!target.is_doc_scrape_eligible(), "{:?}", target);
+            target.set_doc(true);
+            assert!(target.is_doc_scrape_eligible(), "{:?}", target);
+            assert!(target.doc_scrape(), "{:?}", target);
+            target.set_doc_scrape(false);
+            assert!(!target.doc_scrape(), "{:?}", target);
+        }
+    }
+
+    #[test]
+    fn validate_urls() {
+        let mut meta = metadata();
+        meta.homepage = Some("https://example.com".to_string());
+        meta.repository = Some("https://github.com/rust-lang/cargo".to_string());
+        meta.documentation = Some("https://docs.rs/cargo".to_string());
+        assert!(meta.validate_urls().is_empty());
+
+        meta.repository = Some("github.com/rust-lang/cargo".to_string());
+        let problems = meta.validate_urls();
+        assert_eq!(problems.len(), 1);
+        assert!(
+            problems[0].starts_with("repository is not a valid URL: "),
+            "{}",
+            problems[0]
+        );
+    }
+
+    #[test]
+    fn validate_for_publish() {
+        let mut meta = metadata();
+        meta.keywords = vec!["cli".to_string(), "build-tool".to_string()];
+        meta.categories = vec!["development-tools".to_string()];
+        assert!(meta.validate_for_publish().is_ok());
+
+        meta.keywords = (0..6).map(|i| format!("k{}", i)).collect();
+        assert_eq!(
+            meta.validate_for_publish().unwrap_err().to_string(),
+            "expected at most 5 keywords per crate, found 6"
+        );
+
+        meta.keywords = vec!["cli".to_string(), "".to_string()];
+        assert_eq!(
+            meta.validate_for_publish().unwrap_err().to_string(),
+            "keywords cannot be empty strings"
+        );
+
+        meta.keywords = vec!["cli".to_string(), "-cli".to_string()];
+        let err = meta.validate_for_publish().unwrap_err().to_string();
+        assert!(err.starts_with("keyword `-cli` must start"), "{}", err);
+
+        meta.keywords = vec!["cli".to_string()];
+        meta.categories = vec!["".to_string()];
+        assert_eq!(
+            meta.validate_for_publish().unwrap_err().to_string(),
+            "categories cannot be empty strings"
+        );
+    }
+
+    #[test]
+    fn same_kind_and_name() {
+        let a = Target::bin_target("foo", src("a/src/main.rs"), None);
+        let b = Target::bin_target("foo", src("b/src/main.rs"), None);
+        assert!(a.same_kind_and_name(&b));
+        assert!(a != b);
+        assert_eq!(hash_u64(&a), hash_u64(&b));
+
+        let c = Target::bin_target("foo", src("a/src/main.rs"), None);
+        assert_eq!(a, c);
+        assert_eq!(hash_u64(&a), hash_u64(&c));
+
+        let example = Target::example_target("foo", vec![], src("a/src/main.rs"), None);
+        assert!(!a.same_kind_and_name(&example));
+        let other = Target::bin_target("bar", src("a/src/main.rs"), None);
+        assert!(!a.same_kind_and_name(&other));
+    }
+
+    fn workspace_metadata() -> ManifestMetadata {
+        ManifestMetadata {
+            authors: vec!["ws-author".to_string()],
+            keywords: vec!["ws-keyword".to_string()],
+            categories: vec!["ws-category".to_string()],
+            license: Some("MIT".to_string()),
+            license_file: None,
+            description: Some("ws-description".to_string()),
+            readme: Some("ws-README.md".to_string()),
+            homepage: Some("https://ws.example.com".to_string()),
+            repository: Some("https://ws.example.com/repo".to_string()),
+            documentation: Some("https://ws.example.com/docs".to_string()),
+            ..metadata()
+        }
+    }
+
+    #[test]
+    fn inherit_fills_unset_fields() {
+        let ws = workspace_metadata();
+        let m = metadata().inherit(&ws);
+        assert_eq!(m.authors, ws.authors);
+        assert_eq!(m.keywords, ws.keywords);
+        assert_eq!(m.categories, ws.categories);
+        assert_eq!(m.license, ws.license);
+        assert_eq!(m.license_file, None);
+        assert_eq!(m.homepage, ws.homepage);
+        assert_eq!(m.repository, ws.repository);
+        assert_eq!(m.documentation, ws.documentation);
+
+        // Never inherited.
+        assert_eq!(m.description, None);
+        assert_eq!(m.readme, None);
+    }
+
+    #[test]
+    fn inherit_keeps_explicit_fields() {
+        let child = ManifestMetadata {
+            authors: vec!["author".to_string()],
+            keywords: vec!["keyword".to_string()],
+            categories: vec!["category".to_string()],
+            license: Some("Apache-2.0".to_string()),
+            homepage: Some("https://example.com".to_string()),
+            repository: Some("https://example.com/repo".to_string()),
+            documentation: Some("https://example.com/docs".to_string()),
+            ..metadata()
+        };
+        assert_eq!(child.inherit(&workspace_metadata()), child);
+    }
+
+    #[test]
+    fn inherit_license_as_a_pair() {
+        let child = ManifestMetadata {
+            license_file: Some("LICENSE".to_string()),
+            ..metadata()
+        };
+        let m = child.inherit(&workspace_metadata());
+        assert_eq!(m.license, None);
+        assert_eq!(m.license_file, Some("LICENSE".to_string()));
+
+        let ws = ManifestMetadata {
+            license: None,
+            license_file: Some("LICENSE-WS".to_string()),
+            ..workspace_metadata()
+        };
+        let m = metadata().inherit(&ws);
+        assert_eq!(m.license, None);
+        assert_eq!(m.license_file, Some("LICENSE-WS".to_string()));
+    }
+
+    #[test]
+    fn other_lib_kinds_linkable() {
+        assert!(!LibKind::from_str("cdylib").linkable());
+        assert!(LibKind::from_str("cdylib").is_foreign_linkable());
+        assert!(!LibKind::from_str("my-custom-type").is_foreign_linkable());
+
+        let cdylib = Target::lib_target(
+            "foo",
+            vec![LibKind::from_str("cdylib")],
+            src("src/lib.rs"),
+        );
+        assert!(!cdylib.linkable());
+        assert!(cdylib.is_foreign_linkable());
+        let custom = Target::lib_target(
+            "foo",
+            vec![LibKind::from_str("my-custom-type")],
+            src("src/lib.rs"),
+        );
+        assert!(!custom.is_foreign_linkable());
+    }
+
+    #[test]
+    fn is_proc_macro() {
+        let proc_macro = Target::lib_target("foo", vec![LibKind::ProcMacro], src("src/lib.rs"));
+        assert!(proc_macro.is_proc_macro());
+        let rlib = Target::lib_target("foo", vec![LibKind::Rlib], src("src/lib.rs"));
+        assert!(!rlib.is_proc_macro());
+        let bin = Target::bin_target("foo", src("src/main.rs"), None);
+        assert!(!bin.is_proc_macro());
+    }
+
+    #[test]
+    fn relative_src_path() {
+        let root = src("foo");
+        let t = Target::bin_target("foo", root.join("src/bin/foo.rs"), None);
+        assert_eq!(
+            t.relative_src_path(&root),
+            Some(PathBuf::from("src").join("bin").join("foo.rs"))
+        );
+        assert_eq!(t.relative_src_path(&src("bar")), None);
+        assert_eq!(t.relative_src_path(&src("fo")), None);
+    }
+
+    #[test]
+    fn missing_publish_fields() {
+        assert_eq!(
+            metadata().missing_publish_fields(),
+            vec!["description", "license or license-file", "repository"]
+        );
+
+        let meta = ManifestMetadata {
+            description: Some("foo".to_string()),
+            license_file: Some("LICENSE".to_string()),
+            repository: Some("https://example.com/repo".to_string()),
+            ..metadata()
+        };
+        assert!(meta.missing_publish_fields().is_empty());
+
+        let meta = ManifestMetadata {
+            description: Some(String::new()),
+            license: Some("MIT".to_string()),
+            ..meta
+        };
+        assert_eq!(meta.missing_publish_fields(), vec!["description"]);
+    }
+
+    #[test]
+    fn description_named() {
+        let lib = Target::lib_target("foo", vec![LibKind::Lib], src("src/lib.rs"));
+        assert_eq!(lib.description_named(), "library");
+        let bin = Target::bin_target("foo", src("src/main.rs"), None);
+        assert_eq!(bin.description_named(), "binary \"foo\"");
+        let test = Target::test_target("t", src("tests/t.rs"), None);
+        assert_eq!(test.description_named(), "test \"t\"");
+        let bench = Target::bench_target("b", src("benches/b.rs"), None);
+        assert_eq!(bench.description_named(), "bench \"b\"");
+        let example = Target::example_target("ex", vec![], src("examples/ex.rs"), None);
+        assert_eq!(example.description_named(), "example \"ex\"");
+        let example_lib = Target::example_target(
+            "ex",
+            vec![LibKind::Rlib],
+            src("examples/ex.rs"),
+            None,
+        );
+        assert_eq!(example_lib.description_named(), "example \"ex\"");
+        let build = Target::custom_build_target("build-script-build", src("build.rs"));
+        assert_eq!(build.description_named(), "build script");
+    }
+
+    #[test]
+    fn builds_in_mode() {
+        let lib = Target::lib_target("foo", vec![LibKind::Lib], src("src/lib.rs"));
+        let bin = Target::bin_target("foo", src("src/main.rs"), None);
+        let test = Target::test_target("t", src("tests/t.rs"), None);
+        let bench = Target::bench_target("b", src("benches/b.rs"), None);
+        let mut undocumented = lib.clone();
+        undocumented.set_doc(false);
+
+        let test_mode = CompileMode::Test;
+        assert!(lib.builds_in_mode(test_mode));
+        assert!(bin.builds_in_mode(test_mode));
+        assert!(test.builds_in_mode(test_mode));
+        assert!(!bench.builds_in_mode(test_mode));
+
+        let bench_mode = CompileMode::Bench;
+        assert!(lib.builds_in_mode(bench_mode));
+        assert!(bin.builds_in_mode(bench_mode));
+        assert!(!test.builds_in_mode(bench_mode));
+        assert!(bench.builds_in_mode(bench_mode));
+
+        let doc_mode = CompileMode::Doc { deps: false };
+        assert!(lib.builds_in_mode(doc_mode));
+        assert!(!undocumented.builds_in_mode(doc_mode));
+        assert!(bin.builds_in_mode(doc_mode));
+        assert!(!test.builds_in_mode(doc_mode));
+        assert!(!bench.builds_in_mode(doc_mode));
+    }
+
+    #[test]
+    fn with_src_path() {
+        let mut t = Target::example_target(
+            "ex",
+            vec![LibKind::Rlib],
+            src("examples/ex.rs"),
+            Some(vec!["a".to_string()]),
+        );
+        t.set_harness(false).set_doc_scrape(true);
+
+        let moved = t.with_src_path(src("gen/ex.rs"));
+        assert_eq!(moved.src_path(), &*src("gen/ex.rs"));
+        assert_eq!(t.src_path(), &*src("examples/ex.rs"));
+        assert!(moved.same_kind_and_name(&t));
+        assert_eq!(moved.required_features(), t.required_features());
+        assert_eq!(moved.harness(), t.harness());
+        assert_eq!(moved.doc_scrape(), t.doc_scrape());
+        assert_eq!(moved.with_src_path(src("examples/ex.rs")), t);
+    }
+
+    #[test]
+    #[should_panic(expected = "is not absolute")]
+    fn with_src_path_relative() {
+        let t = Target::bin_target("foo", src("src/main.rs"), None);
+        t.with_src_path(PathBuf::from("src/main.rs"));
+    }
+
+    #[test]
+    fn unique_targets() {
+        let lib = |name: &str| Target::lib_target(name, vec![LibKind::Lib], src("src/lib.rs"));
+        let bin = |name: &str| Target::bin_target(name, src("src/main.rs"), None);
+        let example = Target::example_target("foo", vec![], src("examples/foo.rs"), None);
+        assert!(check_unique_targets("foo", &[lib("foo"), bin("foo"), example]).is_ok());
+
+        assert_eq!(
+            check_unique_targets("foo", &[lib("foo"), lib("bar")])
+                .unwrap_err()
+                .to_string(),
+            "package `foo` has 2 library targets, but only one is allowed"
+        );
+        assert_eq!(
+            check_unique_targets("foo", &[lib("foo"), bin("foo"), bin("foo")])
+                .unwrap_err()
+                .to_string(),
+            "found duplicate bin name foo, but all bin targets must have a unique name"
+        );
+    }
+
+    #[test]
+    fn effective_crate_types() {
+        let lib = Target::lib_target("foo", vec![LibKind::Lib], src("src/lib.rs"));
+        assert_eq!(lib.rustc_crate_types(), vec!["lib"]);
+        assert_eq!(lib.effective_crate_types(), vec!["rlib"]);
+
+        let kinds = vec![LibKind::Lib, LibKind::from_str("cdylib"), LibKind::Rlib];
+        let lib = Target::lib_target("foo", kinds, src("src/lib.rs"));
+        assert_eq!(lib.effective_crate_types(), vec!["rlib", "cdylib"]);
+
+        let proc_macro = Target::lib_target("foo", vec![LibKind::ProcMacro], src("src/lib.rs"));
+        assert_eq!(proc_macro.effective_crate_types(), vec!["proc-macro"]);
+
+        let bin = Target::bin_target("foo", src("src/main.rs"), None);
+        assert_eq!(bin.effective_crate_types(), vec!["bin"]);
+    }
+
+    #[test]
+    fn read_readme() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("README.md"), "# foo").unwrap();
+
+        assert_eq!(metadata().read_readme(root.path()).unwrap(), None);
+
+        let meta = ManifestMetadata {
+            readme: Some("README.md".to_string()),
+            ..metadata()
+        };
+        assert_eq!(
+            meta.read_readme(root.path()).unwrap(),
+            Some("# foo".to_string())
+        );
+
+        let meta = ManifestMetadata {
+            readme: Some("MISSING.md".to_string()),
+            ..metadata()
+        };
+        let err = meta.read_readme(root.path()).unwrap_err();
+        assert_eq!(err.to_string(), "failed to read the readme `MISSING.md`");
+    }
+
+    #[test]
+    fn test_harness_modes() {
+        let mut test = Target::test_target("t", src("tests/t.rs"), None);
+        assert!(test.runs_under_test_harness());
+        assert!(!test.runs_as_custom_test());
+
+        test.set_harness(false);
+        assert!(!test.runs_under_test_harness());
+        assert!(test.runs_as_custom_test());
+
+        test.set_tested(false);
+        assert!(!test.runs_under_test_harness());
+        assert!(!test.runs_as_custom_test());
+    }
+
+    #[test]
+    fn doctest_flag() {
+        let mut lib = Target::lib_target("foo", vec![LibKind::Lib], src("src/lib.rs"));
+        assert!(lib.doctest_flag());
+        assert!(lib.doctested());
+        lib.set_doctest(false);
+        assert!(!lib.doctest_flag());
+        assert!(!lib.doctested());
+
+        let mut bin = Target::bin_target("foo", src("src/main.rs"), None);
+        bin.set_doctest(true);
+        assert!(bin.doctest_flag());
+        assert!(!bin.doctested());
+        bin.set_doctest(false);
+        assert!(!bin.doctest_flag());
+        assert!(!bin.doctested());
+    }
+
+    #[test]
+    fn parse_license() {
+        assert_eq!(metadata().parse_license().unwrap(), None);
+
+        let license = |s: &str| ManifestMetadata {
+            license: Some(s.to_string()),
+            ..metadata()
+        };
+
+        let expr = license("MIT OR Apache-2.0")
+            .parse_license()
+            .unwrap()
+            .unwrap();
+        assert_eq!(expr.to_string(), "MIT OR Apache-2.0");
+        assert!(expr.allows(&["MIT"]));
+        assert!(expr.allows(&["Apache-2.0"]));
+        assert!(!expr.allows(&["GPL-3.0"]));
+
+        let expr = license("MIT/Apache-2.0").parse_license().unwrap().unwrap();
+        assert_eq!(expr.to_string(), "MIT OR Apache-2.0");
+
+        let expr = license("(MIT OR Apache-2.0) AND GPL-2.0+ WITH Classpath-exception-2.0")
+            .parse_license()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "(MIT OR Apache-2.0) AND GPL-2.0+ WITH Classpath-exception-2.0"
+        );
+        assert!(expr.allows(&["MIT", "GPL-2.0+"]));
+        assert!(!expr.allows(&["MIT"]));
+
+        let err = license("MIT OR").parse_license().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid SPDX license expression `MIT OR`: \
+             expected a license identifier, found nothing"
+        );
+        let err = license("MIT AND (Apache-2.0").parse_license().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid SPDX license expression `MIT AND (Apache-2.0`: \
+             expected `)`, but the expression ended"
+        );
+        let err = license("MIT Apache-2.0").parse_license().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid SPDX license expression `MIT Apache-2.0`: \
+             expected `AND`, `OR` or the end of the expression, \
+             found a license identifier"
+        );
+    }
+
+    #[test]
+    fn set_required_features() {
+        let mut bin = Target::bin_target("foo", src("src/main.rs"), None);
+        assert_eq!(bin.required_features(), None);
+
+        bin.set_required_features(Some(vec!["a".to_string(), "b".to_string()]))
+            .set_tested(false);
+        assert_eq!(
+            bin.required_features(),
+            Some(&vec!["a".to_string(), "b".to_string()])
+        );
+        assert!(!bin.tested());
+
+        bin.set_required_features(None);
+        assert_eq!(bin.required_features(), None);
+    }
+
+    #[test]
+    fn duplicate_target_names() {
+        let lib = Target::lib_target("foo", vec![LibKind::Lib], src("src/lib.rs"));
+        let bin = |name: &str| Target::bin_target(name, src("src/main.rs"), None);
+        let example = Target::example_target("foo", vec![], src("examples/foo.rs"), None);
+        let targets = vec![lib, bin("foo"), example];
+        assert!(find_duplicate_target_names(&targets).is_empty());
+
+        let targets = vec![bin("foo"), bin("bar"), bin("foo")];
+        assert_eq!(
+            find_duplicate_target_names(&targets),
+            vec![("foo".to_string(), vec![&TargetKind::Bin, &TargetKind::Bin])]
+        );
+    }
+
+    #[test]
+    fn crate_type_arg() {
+        let kinds = vec![LibKind::Rlib, LibKind::from_str("cdylib")];
+        let lib = Target::lib_target("foo", kinds, src("src/lib.rs"));
+        assert_eq!(lib.crate_type_arg(), Some("rlib,cdylib".to_string()));
+
+        let lib = Target::lib_target("foo", vec![LibKind::Lib], src("src/lib.rs"));
+        assert_eq!(lib.crate_type_arg(), Some("lib".to_string()));
+
+        let bin = Target::bin_target("foo", src("src/main.rs"), None);
+        assert_eq!(bin.crate_type_arg(), None);
+    }
+
+    #[test]
+    fn example_harness() {
+        let example = Target::example_target("e", vec![], src("examples/e.rs"), None);
+        assert!(example.harness());
+
+        let example =
+            Target::example_target_with_harness("e", vec![], src("examples/e.rs"), None, false);
+        assert!(!example.harness());
+    }
+
+    #[test]
+    fn push_lib_kind() {
+        let mut lib = Target::lib_target("foo", vec![LibKind::Rlib], src("src/lib.rs"));
+        lib.push_lib_kind(LibKind::Other("cdylib".to_string())).unwrap();
+        lib.push_lib_kind(LibKind::Rlib).unwrap();
+        assert_eq!(lib.rustc_crate_types(), vec!["rlib", "cdylib"]);
+
+        let mut bin = Target::bin_target("foo", src("src/main.rs"), None);
+        let err = bin.push_lib_kind(LibKind::Other("cdylib".to_string())).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "cannot add crate type `cdylib` to binary \"foo\", which is not a library"
+        );
+    }
+
+    #[test]
+    fn cmp_stable() {
+        let a = Target::bin_target("foo", src("one/src/main.rs"), None);
+        let b = Target::bin_target("foo", src("two/src/main.rs"), None);
+        assert_eq!(a.cmp_stable(&b), Ordering::Equal);
+
+        let lib = Target::lib_target("foo", vec![LibKind::Lib], src("src/lib.rs"));
+        let bin = Target::bin_target("bar", src("src/bin/bar.rs"), None);
+        let mut targets = vec![b, bin, lib];
+        targets.sort_by(|a, b| a.cmp_stable(b));
+        let names = targets.iter().map(|t| t.description_named()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["library", "binary \"bar\"", "binary \"foo\""]);
+    }
+
+    #[test]
+    fn badge_urls() {
+        let badge = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|&(k, v)| (k.to_string(), v.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let mut metadata = metadata();
+        metadata.badges.insert(
+            "travis-ci".to_string(),
+            badge(&[("repository", "rust-lang/cargo"), ("branch", "master")]),
+        );
+        metadata.badges.insert(
+            "maintenance".to_string(),
+            badge(&[("status", "actively-developed")]),
+        );
+        metadata
+            .badges
+            .insert("unknown".to_string(), badge(&[("repository", "a/b")]));
+        let urls = metadata
+            .badge_urls()
+            .into_iter()
+            .map(|(provider, url)| (provider, url.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            urls,
+            vec![
+                (
+                    "maintenance".to_string(),
+                    "https://img.shields.io/badge/maintenance-actively--developed-brightgreen.svg"
+                        .to_string(),
+                ),
+                (
+                    "travis-ci".to_string(),
+                    "https://img.shields.io/travis/rust-lang/cargo/master.svg".to_string(),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn sanitized_crate_name() {
+        let bin = Target::bin_target("foo-bar", src("src/main.rs"), None);
+        assert_eq!(bin.sanitized_crate_name().unwrap(), "foo_bar");
+
+        let digit = Target::bin_target("2048", src("src/main.rs"), None);
+        assert_eq!(
+            digit.sanitized_crate_name().unwrap_err().to_string(),
+            "the name of binary \"2048\" starts with a digit, so it cannot be used as a crate name"
+        );
+
+        let spaces = Target::example_target("my example", vec![], src("examples/e.rs"), None);
+        assert_eq!(
+            spaces.sanitized_crate_name().unwrap_err().to_string(),
+            "invalid character ` ` in the name of example \"my example\", \
+             which cannot be used as a crate name"
+        );
+    }
+
+    #[test]
+    fn set_kind() {
+        let features = Some(vec!["a".to_string()]);
+        let mut target = Target::bin_target("foo", src("src/bin/foo.rs"), features.clone());
+        target.set_kind(TargetKind::ExampleBin).unwrap();
+        assert!(target.is_example());
+        assert_eq!(target.name(), "foo");
+        assert_eq!(target.src_path(), &*src("src/bin/foo.rs"));
+        assert_eq!(target.required_features(), features.as_ref());
+        assert!(!target.tested());
+        assert!(!target.documented());
+
+        let mut build = Target::custom_build_target("build-script-build", src("build.rs"));
+        let err = build.set_kind(TargetKind::Bin).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "cannot change build script into a bin target, only binaries, examples, \
+             tests and benchmarks can be changed into one another"
+        );
+        assert!(build.is_custom_build());
+        assert!(build.for_host());
+
+        let mut bin = Target::bin_target("foo", src("src/main.rs"), None);
+        let err = bin.set_kind(TargetKind::ExampleLib(vec![])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "cannot change binary \"foo\" into a library example without any crate types"
+        );
+        assert!(bin.is_bin());
+    }
+
+    #[test]
+    fn content_hash() {
+        let a = Target::lib_target("foo", vec![LibKind::Rlib], src("one/src/lib.rs"));
+        let b = Target::lib_target("foo", vec![LibKind::Rlib], src("two/src/lib.rs"));
+        assert_eq!(a.content_hash(b"fn f() {}"), b.content_hash(b"fn f() {}"));
+        assert_ne!(a.content_hash(b"fn f() {}"), a.content_hash(b"fn g() {}"));
+
+        let mut edition2018 = a.clone();
+        edition2018.set_edition(Edition::Edition2018);
+        assert_ne!(a.content_hash(b"fn f() {}"), edition2018.content_hash(b"fn f() {}"));
+
+        let cdylib = Target::lib_target(
+            "foo",
+            vec![LibKind::Rlib, LibKind::Other("cdylib".to_string())],
+            src("one/src/lib.rs"),
+        );
+        assert_ne!(a.content_hash(b"fn f() {}"), cdylib.content_hash(b"fn f() {}"));
+
+        let mut untested = a.clone();
+        untested.set_tested(false);
+        assert_ne!(a.content_hash(b"fn f() {}"), untested.content_hash(b"fn f() {}"));
+    }
 }
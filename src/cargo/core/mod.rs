@@ -1,8 +1,9 @@
 pub use self::dependency::Dependency;
 pub use self::features::{CliUnstable, Edition, Feature, Features};
 pub use self::features::{maybe_allow_nightly_features, enable_nightly_features};
+pub use self::interning::InternedString;
 pub use self::manifest::{EitherManifest, VirtualManifest};
-pub use self::manifest::{LibKind, Manifest, Target, TargetKind};
+pub use self::manifest::{LibKind, Manifest, PackageMatcher, Target, TargetKind};
 pub use self::package::{Package, PackageSet};
 pub use self::package_id::PackageId;
 pub use self::package_id_spec::PackageIdSpec;
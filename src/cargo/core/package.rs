@@ -4,7 +4,7 @@ use std::fmt;
 use std::hash;
 use std::path::{Path, PathBuf};
 
-use semver::Version;
+use semver::{Version, VersionReq};
 use serde::ser;
 use toml;
 use lazycell::LazyCell;
@@ -47,6 +47,8 @@ struct SerializedPackage<'a> {
     keywords: &'a [String],
     readme: Option<&'a str>,
     repository: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rust_version: Option<&'a VersionReq>,
 }
 
 impl ser::Serialize for Package {
@@ -84,6 +86,7 @@ impl ser::Serialize for Package {
             keywords,
             readme,
             repository,
+            rust_version: self.manifest.rust_version(),
         }.serialize(s)
     }
 }
@@ -105,6 +108,10 @@ impl Package {
     pub fn manifest(&self) -> &Manifest {
         &self.manifest
     }
+    /// Get the manifest, mutably
+    pub fn manifest_mut(&mut self) -> &mut Manifest {
+        &mut self.manifest
+    }
     /// Get the path to the manifest
     pub fn manifest_path(&self) -> &Path {
         &self.manifest_path
@@ -144,7 +151,7 @@ impl Package {
 
     /// Whether the package uses a custom build script for any target
     pub fn has_custom_build(&self) -> bool {
-        self.targets().iter().any(|t| t.is_custom_build())
+        self.manifest.has_custom_build()
     }
 
     pub fn find_closest_target(
@@ -170,7 +177,8 @@ impl Package {
     }
 
     pub fn to_registry_toml(&self, config: &Config) -> CargoResult<String> {
-        let manifest = self.manifest().original().prepare_for_publish(config)?;
+        let mut manifest = self.manifest().original().prepare_for_publish(config)?;
+        manifest.inherit_metadata(self.manifest().metadata());
         let toml = toml::to_string(&manifest)?;
         Ok(format!(
             "\
@@ -7,6 +7,7 @@ use std::slice;
 use glob::glob;
 use url::Url;
 
+use core::manifest::ManifestMetadata;
 use core::profiles::Profiles;
 use core::registry::PackageRegistry;
 use core::{Dependency, PackageIdSpec};
@@ -114,6 +115,7 @@ pub struct WorkspaceRootConfig {
     members: Option<Vec<String>>,
     default_members: Option<Vec<String>>,
     exclude: Vec<String>,
+    package: Option<ManifestMetadata>,
 }
 
 /// An iterator over the member packages of a workspace, returned by
@@ -151,6 +153,7 @@ impl<'cfg> Workspace<'cfg> {
         ws.root_manifest = ws.find_root(manifest_path)?;
         ws.find_members()?;
         ws.validate()?;
+        ws.inherit_metadata();
         Ok(ws)
     }
 
@@ -513,6 +516,27 @@ impl<'cfg> Workspace<'cfg> {
         Ok(())
     }
 
+    /// Fills in the metadata each member leaves unset from the root's
+    /// `[workspace.package]` table, if it has one.
+    fn inherit_metadata(&mut self) {
+        let root_manifest_path = match self.root_manifest {
+            Some(ref path) => path,
+            None => return,
+        };
+        let inherited = match *self.packages.get(root_manifest_path).workspace_config() {
+            WorkspaceConfig::Root(ref root_config) => match root_config.package {
+                Some(ref package) => package.clone(),
+                None => return,
+            },
+            WorkspaceConfig::Member { .. } => return,
+        };
+        for path in self.members.iter() {
+            if let MaybePackage::Package(ref mut p) = *self.packages.get_mut(path) {
+                p.manifest_mut().inherit_metadata(&inherited);
+            }
+        }
+    }
+
     /// Validates a workspace, ensuring that a number of invariants are upheld:
     ///
     /// 1. A workspace only has one root.
@@ -756,6 +780,10 @@ impl<'cfg> Packages<'cfg> {
         self.packages.get(manifest_path.parent().unwrap())
     }
 
+    fn get_mut(&mut self, manifest_path: &Path) -> &mut MaybePackage {
+        self.packages.get_mut(manifest_path.parent().unwrap()).unwrap()
+    }
+
     fn load(&mut self, manifest_path: &Path) -> CargoResult<&MaybePackage> {
         let key = manifest_path.parent().unwrap();
         match self.packages.entry(key.to_path_buf()) {
@@ -817,15 +845,32 @@ impl WorkspaceRootConfig {
         members: &Option<Vec<String>>,
         default_members: &Option<Vec<String>>,
         exclude: &Option<Vec<String>>,
+        package: Option<ManifestMetadata>,
     ) -> WorkspaceRootConfig {
         WorkspaceRootConfig {
             root_dir: root_dir.to_path_buf(),
             members: members.clone(),
             default_members: default_members.clone(),
             exclude: exclude.clone().unwrap_or_default(),
+            package,
         }
     }
 
+    /// The `members` list, if one was given.
+    pub fn members(&self) -> Option<&[String]> {
+        self.members.as_ref().map(|m| &m[..])
+    }
+
+    /// The `default-members` list, if one was given.
+    pub fn default_members(&self) -> Option<&[String]> {
+        self.default_members.as_ref().map(|m| &m[..])
+    }
+
+    /// The `[workspace.package]` metadata members inherit, if any.
+    pub fn package(&self) -> Option<&ManifestMetadata> {
+        self.package.as_ref()
+    }
+
     /// Checks the path against the `excluded` list.
     ///
     /// This method does NOT consider the `members` list.
@@ -643,7 +643,7 @@ fn generate_targets<'a>(
     Ok(units)
 }
 
-fn resolve_all_features(
+pub fn resolve_all_features(
     resolve_with_overrides: &Resolve,
     package_id: &PackageId,
 ) -> HashSet<String> {
@@ -664,21 +664,15 @@ fn resolve_all_features(
 /// that are automatically included when the user doesn't specify any targets.
 fn generate_default_targets(targets: &[Target], mode: CompileMode) -> Vec<&Target> {
     match mode {
-        CompileMode::Bench => targets.iter().filter(|t| t.benched()).collect(),
-        CompileMode::Test => targets
-            .iter()
-            .filter(|t| t.tested() || t.is_example())
-            .collect(),
-        CompileMode::Build | CompileMode::Check { .. } => targets
-            .iter()
-            .filter(|t| t.is_bin() || t.is_lib())
-            .collect(),
+        CompileMode::Bench | CompileMode::Test | CompileMode::Build | CompileMode::Check { .. } => {
+            targets.iter().filter(|t| t.builds_in_mode(mode)).collect()
+        }
         CompileMode::Doc { .. } => {
             // `doc` does lib and bins (bin with same name as lib is skipped).
             targets
                 .iter()
                 .filter(|t| {
-                    t.documented()
+                    t.builds_in_mode(mode)
                         && (!t.is_bin()
                             || !targets.iter().any(|l| l.is_lib() && l.name() == t.name()))
                 })
@@ -1,8 +1,46 @@
+use std::collections::HashSet;
 use std::path::Path;
 
 use ops;
+use ops::cargo_compile::resolve_all_features;
 use util::{self, CargoResult, ProcessError};
-use core::{TargetKind, Workspace};
+use core::{InternedString, Package, TargetKind, Workspace};
+
+/// The binary `cargo run` runs for the package `options` selects when
+/// neither `--bin` nor `--example` is given, see
+/// `Manifest::default_runnable_target`.
+pub fn default_run(ws: &Workspace, options: &ops::CompileOptions) -> CargoResult<Option<String>> {
+    let pkg = match options.get_package(ws)? {
+        Some(pkg) => pkg,
+        None => return Ok(None),
+    };
+    let enabled = enabled_features(ws, pkg, options)?;
+    let target = pkg.manifest().default_runnable_target(&enabled)?;
+    Ok(Some(target.name().to_string()))
+}
+
+/// The features of `pkg` activated by `--features`, `--all-features` and
+/// `--no-default-features`, including those of its dependencies, the same
+/// way `ops::compile` works them out to check `required-features`.
+fn enabled_features(
+    ws: &Workspace,
+    pkg: &Package,
+    options: &ops::CompileOptions,
+) -> CargoResult<HashSet<InternedString>> {
+    let specs = options.spec.into_package_id_specs(ws)?;
+    let (_, resolve) = ops::resolve_ws_precisely(
+        ws,
+        None,
+        &options.features,
+        options.all_features,
+        options.no_default_features,
+        &specs,
+    )?;
+    Ok(resolve_all_features(&resolve, pkg.package_id())
+        .iter()
+        .map(|f| InternedString::new(f))
+        .collect())
+}
 
 pub fn run(
     ws: &Workspace,
@@ -14,52 +52,34 @@ pub fn run(
     let pkg = options.get_package(ws)?
         .unwrap_or_else(|| unreachable!("cargo run supports single package only"));
 
-    // We compute the `bins` here *just for diagnosis*.  The actual set of packages to be run
+    // We check the binaries here *just for diagnosis*.  The actual set of packages to be run
     // is determined by the `ops::compile` call below.
-    let bins: Vec<_> = pkg.manifest()
-        .targets()
-        .iter()
-        .filter(|a| {
-            !a.is_lib() && !a.is_custom_build() && if !options.filter.is_specific() {
-                a.is_bin()
-            } else {
-                options.filter.target_run(a)
-            }
-        })
-        .map(|bin| (bin.name(), bin.kind()))
-        .collect();
+    if !options.filter.is_specific() {
+        let enabled = enabled_features(ws, pkg, options)?;
+        pkg.manifest().default_runnable_target(&enabled)?;
+    } else {
+        let bins: Vec<_> = pkg.manifest()
+            .targets()
+            .iter()
+            .filter(|a| !a.is_lib() && !a.is_custom_build() && options.filter.target_run(a))
+            .map(|bin| (bin.name(), bin.kind()))
+            .collect();
 
-    if bins.is_empty() {
-        if !options.filter.is_specific() {
-            bail!("a bin target must be available for `cargo run`")
-        } else {
-            // this will be verified in cargo_compile
+        // An empty `bins` will be verified in cargo_compile
+        if bins.len() == 1 {
+            let &(name, kind) = bins.first().unwrap();
+            match kind {
+                &TargetKind::ExampleLib(..) => { 
+                    bail!(
+                        "example target `{}` is a library and cannot be executed",
+                        name
+                    ) 
+                },
+                _ => { }
+            };
         }
-    }
 
-    if bins.len() == 1 {
-        let &(name, kind) = bins.first().unwrap();
-        match kind {
-            &TargetKind::ExampleLib(..) => { 
-                bail!(
-                    "example target `{}` is a library and cannot be executed",
-                    name
-                ) 
-            },
-            _ => { }
-        };
-    }
-
-    if bins.len() > 1 {
-        if !options.filter.is_specific() {
-            let names: Vec<&str> = bins.into_iter().map(|bin| bin.0).collect();
-            bail!(
-                "`cargo run` requires that a project only have one \
-                 executable; use the `--bin` option to specify which one \
-                 to run\navailable binaries: {}",
-                 names.join(", ")
-            )
-        } else {
+        if bins.len() > 1 {
             bail!(
                 "`cargo run` can run at most one executable, but \
                  multiple were specified"
@@ -18,7 +18,6 @@ use core::manifest::ManifestMetadata;
 use ops;
 use sources::RegistrySource;
 use util::config::{self, Config};
-use util::paths;
 use util::ToUrl;
 use util::errors::{CargoResult, CargoResultExt};
 use util::important_paths::find_root_manifest_for_wd;
@@ -43,17 +42,16 @@ pub struct PublishOpts<'cfg> {
 pub fn publish(ws: &Workspace, opts: &PublishOpts) -> CargoResult<()> {
     let pkg = ws.current()?;
 
-    if let Some(ref allowed_registries) = *pkg.publish() {
-        if !match opts.registry {
-            Some(ref registry) => allowed_registries.contains(registry),
-            None => false,
-        } {
-            bail!(
-                "some crates cannot be published.\n\
-                 `{}` is marked as unpublishable",
-                pkg.name()
-            );
-        }
+    let allowed = match opts.registry {
+        Some(ref registry) => pkg.manifest().can_publish_to(registry),
+        None => pkg.publish().is_none(),
+    };
+    if !allowed {
+        bail!(
+            "some crates cannot be published.\n\
+             `{}` is marked as unpublishable",
+            pkg.name()
+        );
     }
 
     if !pkg.manifest().patch().is_empty() {
@@ -192,10 +190,7 @@ fn transmit(
         ref badges,
         ref links,
     } = *manifest.metadata();
-    let readme_content = match *readme {
-        Some(ref readme) => Some(paths::read(&pkg.root().join(readme))?),
-        None => None,
-    };
+    let readme_content = manifest.metadata().read_readme(pkg.root())?;
     if let Some(ref file) = *license_file {
         if fs::metadata(&pkg.root().join(file)).is_err() {
             bail!("the license file `{}` does not exist", file)
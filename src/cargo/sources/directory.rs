@@ -1,16 +1,12 @@
 use std::collections::HashMap;
 use std::fmt::{self, Debug, Formatter};
-use std::fs::File;
-use std::io::Read;
 use std::path::{Path, PathBuf};
 
-use hex;
-
 use serde_json;
 
 use core::{Dependency, Package, PackageId, Source, SourceId, Summary};
 use sources::PathSource;
-use util::{Config, Sha256};
+use util::{to_hex, Config, Sha256};
 use util::errors::{CargoResult, CargoResultExt};
 use util::paths;
 
@@ -168,23 +164,12 @@ impl<'cfg> Source for DirectorySource<'cfg> {
             None => bail!("failed to find entry for `{}` in directory source", id),
         };
 
-        let mut buf = [0; 16 * 1024];
         for (file, cksum) in cksum.files.iter() {
-            let mut h = Sha256::new();
             let file = pkg.root().join(file);
-
-            (|| -> CargoResult<()> {
-                let mut f = File::open(&file)?;
-                loop {
-                    match f.read(&mut buf)? {
-                        0 => return Ok(()),
-                        n => h.update(&buf[..n]),
-                    }
-                }
-            })()
+            let actual = Sha256::file(&file)
                 .chain_err(|| format!("failed to calculate checksum of: {}", file.display()))?;
 
-            let actual = hex::encode(h.finish());
+            let actual = to_hex(&actual);
             if &*actual != cksum {
                 bail!(
                     "\
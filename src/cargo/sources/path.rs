@@ -4,7 +4,6 @@ use std::path::{Path, PathBuf};
 
 use filetime::FileTime;
 use git2;
-use glob::Pattern;
 use ignore::Match;
 use ignore::gitignore::GitignoreBuilder;
 
@@ -116,42 +115,7 @@ impl<'cfg> PathSource<'cfg> {
 
         // glob-like matching rules
 
-        let glob_parse = |p: &String| {
-            let pattern: &str = if p.starts_with('/') {
-                &p[1..p.len()]
-            } else {
-                p
-            };
-            Pattern::new(pattern)
-                .map_err(|e| format_err!("could not parse glob pattern `{}`: {}", p, e))
-        };
-
-        let glob_exclude = pkg.manifest()
-            .exclude()
-            .iter()
-            .map(|p| glob_parse(p))
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let glob_include = pkg.manifest()
-            .include()
-            .iter()
-            .map(|p| glob_parse(p))
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let glob_should_package = |relative_path: &Path| -> bool {
-            fn glob_match(patterns: &Vec<Pattern>, relative_path: &Path) -> bool {
-                patterns
-                    .iter()
-                    .any(|pattern| pattern.matches_path(relative_path))
-            }
-
-            // include and exclude options are mutually exclusive.
-            if no_include_option {
-                !glob_match(&glob_exclude, relative_path)
-            } else {
-                glob_match(&glob_include, relative_path)
-            }
-        };
+        let glob_matcher = pkg.manifest().package_matcher();
 
         // ignore-like matching rules
 
@@ -198,7 +162,7 @@ impl<'cfg> PathSource<'cfg> {
 
         let mut filter = |path: &Path| -> CargoResult<bool> {
             let relative_path = util::without_prefix(path, root).unwrap();
-            let glob_should_package = glob_should_package(relative_path);
+            let glob_should_package = glob_matcher.should_package(relative_path);
             let ignore_should_package = ignore_should_package(relative_path)?;
 
             if glob_should_package != ignore_should_package {
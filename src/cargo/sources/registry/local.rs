@@ -1,13 +1,10 @@
-use std::io::SeekFrom;
-use std::io::prelude::*;
 use std::path::Path;
 
 use core::PackageId;
-use hex;
 use sources::registry::{RegistryConfig, RegistryData};
 use util::FileLock;
 use util::paths;
-use util::{Config, Filesystem, Sha256};
+use util::{from_hex, verify_file_sha256, Config, Filesystem};
 use util::errors::{CargoResult, CargoResultExt};
 
 pub struct LocalRegistry<'cfg> {
@@ -72,7 +69,7 @@ impl<'cfg> RegistryData for LocalRegistry<'cfg> {
 
     fn download(&mut self, pkg: &PackageId, checksum: &str) -> CargoResult<FileLock> {
         let crate_file = format!("{}-{}.crate", pkg.name(), pkg.version());
-        let mut crate_file = self.root.open_ro(&crate_file, self.config, "crate file")?;
+        let crate_file = self.root.open_ro(&crate_file, self.config, "crate file")?;
 
         // If we've already got an unpacked version of this crate, then skip the
         // checksum below as it is in theory already verified.
@@ -85,22 +82,13 @@ impl<'cfg> RegistryData for LocalRegistry<'cfg> {
 
         // We don't actually need to download anything per-se, we just need to
         // verify the checksum matches the .crate file itself.
-        let mut state = Sha256::new();
-        let mut buf = [0; 64 * 1024];
-        loop {
-            let n = crate_file
-                .read(&mut buf)
-                .chain_err(|| format!("failed to read `{}`", crate_file.path().display()))?;
-            if n == 0 {
-                break;
-            }
-            state.update(&buf[..n]);
+        let mut expected = [0; 32];
+        match from_hex(checksum) {
+            Ok(ref bytes) if bytes.len() == expected.len() => expected.copy_from_slice(bytes),
+            _ => bail!("invalid checksum `{}` for `{}`", checksum, pkg),
         }
-        if hex::encode(state.finish()) != checksum {
-            bail!("failed to verify the checksum of `{}`", pkg)
-        }
-
-        crate_file.seek(SeekFrom::Start(0))?;
+        verify_file_sha256(crate_file.path(), &expected)
+            .chain_err(|| format!("failed to verify the checksum of `{}`", pkg))?;
 
         Ok(crate_file)
     }
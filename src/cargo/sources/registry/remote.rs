@@ -7,7 +7,6 @@ use std::path::Path;
 use std::str;
 
 use git2;
-use hex;
 use serde_json;
 use lazycell::LazyCell;
 
@@ -278,7 +277,7 @@ impl<'cfg> RegistryData for RemoteRegistry<'cfg> {
         })?;
 
         // Verify what we just downloaded
-        if hex::encode(state.finish()) != checksum {
+        if state.finish_hex() != checksum {
             bail!("failed to verify the checksum of `{}`", pkg)
         }
 
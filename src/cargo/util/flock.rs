@@ -220,12 +220,12 @@ impl Filesystem {
             .chain_err(|| format!("failed to open: {}", path.display()))?;
         match state {
             State::Exclusive => {
-                acquire(config, msg, &path, &|| f.try_lock_exclusive(), &|| {
+                acquire(config, msg, &path, &|| fs2::FileExt::try_lock_exclusive(&f), &|| {
                     f.lock_exclusive()
                 })?;
             }
             State::Shared => {
-                acquire(config, msg, &path, &|| f.try_lock_shared(), &|| {
+                acquire(config, msg, &path, &|| fs2::FileExt::try_lock_shared(&f), &|| {
                     f.lock_shared()
                 })?;
             }
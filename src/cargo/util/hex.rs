@@ -3,8 +3,33 @@
 use hex;
 use std::hash::{Hash, Hasher, SipHasher};
 
-pub fn to_hex(num: u64) -> String {
-    hex::encode(&[
+/// Why `from_hex` couldn't decode a string.
+#[derive(Debug, Fail, PartialEq)]
+pub enum HexError {
+    #[fail(display = "odd number of hex digits")]
+    OddLength,
+    #[fail(display = "invalid hex digit `{}` at index {}", c, index)]
+    InvalidDigit { c: char, index: usize },
+}
+
+/// Encodes `bytes` as lowercase hex.
+pub fn to_hex(bytes: &[u8]) -> String {
+    hex::encode(bytes)
+}
+
+/// Decodes the hex string `s`, accepting digits of either case.
+pub fn from_hex(s: &str) -> Result<Vec<u8>, HexError> {
+    if s.len() % 2 != 0 {
+        return Err(HexError::OddLength);
+    }
+    if let Some((index, c)) = s.char_indices().find(|&(_, c)| !c.is_digit(16)) {
+        return Err(HexError::InvalidDigit { c, index });
+    }
+    Ok(hex::decode(s).expect("validated hex"))
+}
+
+pub fn u64_to_hex(num: u64) -> String {
+    to_hex(&[
         (num >> 0) as u8,
         (num >> 8) as u8,
         (num >> 16) as u8,
@@ -23,5 +48,43 @@ pub fn hash_u64<H: Hash>(hashable: &H) -> u64 {
 }
 
 pub fn short_hash<H: Hash>(hashable: &H) -> String {
-    to_hex(hash_u64(hashable))
+    u64_to_hex(hash_u64(hashable))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_hex, to_hex, u64_to_hex, HexError};
+
+    #[test]
+    fn round_trip() {
+        let bytes = [0x00, 0x01, 0x7f, 0x80, 0xab, 0xff];
+        assert_eq!(to_hex(&bytes), "00017f80abff");
+        assert_eq!(from_hex("00017f80abff").unwrap(), bytes.to_vec());
+        assert_eq!(from_hex("00017F80ABFF").unwrap(), bytes.to_vec());
+        assert_eq!(to_hex(&[]), "");
+        assert_eq!(from_hex("").unwrap(), Vec::<u8>::new());
+        assert_eq!(u64_to_hex(0x0102), "0201000000000000");
+    }
+
+    #[test]
+    fn rejects_odd_length() {
+        assert_eq!(from_hex("abc"), Err(HexError::OddLength));
+        assert_eq!(from_hex("0").unwrap_err().to_string(), "odd number of hex digits");
+    }
+
+    #[test]
+    fn rejects_non_hex() {
+        assert_eq!(
+            from_hex("0g"),
+            Err(HexError::InvalidDigit { c: 'g', index: 1 })
+        );
+        assert_eq!(
+            from_hex("zz").unwrap_err().to_string(),
+            "invalid hex digit `z` at index 0"
+        );
+        assert_eq!(
+            from_hex("0é0"),
+            Err(HexError::InvalidDigit { c: 'é', index: 1 })
+        );
+    }
 }
@@ -6,13 +6,14 @@ pub use self::errors::{CargoTestError, CliError, ProcessError};
 pub use self::errors::{internal, process_error};
 pub use self::flock::{FileLock, Filesystem};
 pub use self::graph::Graph;
-pub use self::hex::{short_hash, to_hex, hash_u64};
+pub use self::hex::{from_hex, short_hash, to_hex, u64_to_hex, hash_u64, HexError};
 pub use self::lev_distance::lev_distance;
 pub use self::paths::{dylib_path, join_paths, bytes2path, path2bytes};
 pub use self::paths::{dylib_path_envvar, normalize_path, without_prefix};
 pub use self::process_builder::{process, ProcessBuilder};
 pub use self::rustc::Rustc;
-pub use self::sha256::Sha256;
+pub use self::sha256::{hasher_for, verify_file_sha256, Digest256, DynHasher, Sha256};
+pub use self::spdx::SpdxExpr;
 pub use self::to_semver::ToSemver;
 pub use self::to_url::ToUrl;
 pub use self::vcs::{FossilRepo, GitRepo, HgRepo, PijulRepo};
@@ -38,6 +39,7 @@ mod cfg;
 mod dependency_queue;
 mod rustc;
 mod sha256;
+mod spdx;
 mod vcs;
 mod flock;
 mod read2;
@@ -1,23 +1,306 @@
 extern crate crypto_hash;
 use self::crypto_hash::{Algorithm, Hasher};
-use std::io::Write;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
 
-pub struct Sha256(Hasher);
+use util::errors::CargoResult;
+use util::hex::to_hex;
+
+pub struct Sha256 {
+    hasher: Hasher,
+    // `crypto_hash::Hasher` isn't `Clone`, so everything fed in since the
+    // last `finish` is kept around to be replayed into a fresh hasher when
+    // cloning.
+    fed: Vec<u8>,
+}
 
 impl Sha256 {
     pub fn new() -> Sha256 {
         let hasher = Hasher::new(Algorithm::SHA256);
-        Sha256(hasher)
+        Sha256 {
+            hasher,
+            fed: Vec::new(),
+        }
     }
 
     pub fn update(&mut self, bytes: &[u8]) {
-        let _ = self.0.write_all(bytes);
+        let _ = self.hasher.write_all(bytes);
+        self.fed.extend_from_slice(bytes);
     }
 
     pub fn finish(&mut self) -> [u8; 32] {
-        let mut ret = [0u8; 32];
-        let data = self.0.finish();
-        ret.copy_from_slice(&data[..]);
+        self.fed.clear();
+        to_array(&self.hasher.finish())
+    }
+
+    /// Like `finish`, but returns a `Digest256` to compare in constant time.
+    pub fn finish_digest(&mut self) -> Digest256 {
+        Digest256::from(self.finish())
+    }
+
+    /// Like `finish`, but returns the digest as lowercase hex.
+    pub fn finish_hex(&mut self) -> String {
+        to_hex(&self.finish())
+    }
+
+    /// Discards everything fed so far so the hasher can be reused.
+    pub fn reset(&mut self) {
+        self.hasher = Hasher::new(Algorithm::SHA256);
+        self.fed.clear();
+    }
+
+    /// Like `finish`, but consumes the hasher.
+    pub fn digest(mut self) -> [u8; 32] {
+        self.finish()
+    }
+
+    /// Hashes the contents of the file at `path`, reading it in chunks.
+    ///
+    /// This streams straight into the underlying hasher rather than through
+    /// a `Sha256`, so the contents aren't buffered for `clone`.
+    pub fn file(path: &Path) -> io::Result<[u8; 32]> {
+        let mut f = File::open(path).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("failed to open `{}`: {}", path.display(), e),
+            )
+        })?;
+        let mut hasher = Hasher::new(Algorithm::SHA256);
+        io::copy(&mut f, &mut hasher)?;
+        Ok(to_array(&hasher.finish()))
+    }
+}
+
+impl Clone for Sha256 {
+    fn clone(&self) -> Sha256 {
+        let mut ret = Sha256::new();
+        ret.update(&self.fed);
         ret
     }
 }
+
+fn to_array(data: &[u8]) -> [u8; 32] {
+    let mut ret = [0u8; 32];
+    ret.copy_from_slice(data);
+    ret
+}
+
+/// Hashes the file at `path` and checks that its digest is `expected`.
+pub fn verify_file_sha256(path: &Path, expected: &[u8; 32]) -> CargoResult<()> {
+    let actual = Sha256::file(path)?;
+    if !Digest256::from(actual).ct_eq(&Digest256::from(*expected)) {
+        bail!(
+            "checksum mismatch for `{}`:\n\
+             expected: {}\n\
+             actual:   {}",
+            path.display(),
+            to_hex(expected),
+            to_hex(&actual)
+        )
+    }
+    Ok(())
+}
+
+/// A SHA-256 digest, compared in constant time so that checking it against
+/// an expected value doesn't leak how many leading bytes matched.
+#[derive(Clone, Copy, Debug)]
+pub struct Digest256(pub [u8; 32]);
+
+impl Digest256 {
+    pub fn ct_eq(&self, other: &Digest256) -> bool {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+    }
+}
+
+impl From<[u8; 32]> for Digest256 {
+    fn from(bytes: [u8; 32]) -> Digest256 {
+        Digest256(bytes)
+    }
+}
+
+/// A hash function chosen at runtime, see `hasher_for`.
+pub trait DynHasher {
+    fn update(&mut self, bytes: &[u8]);
+    fn finish(self: Box<Self>) -> Vec<u8>;
+}
+
+impl DynHasher for Sha256 {
+    fn update(&mut self, bytes: &[u8]) {
+        Sha256::update(self, bytes)
+    }
+
+    fn finish(mut self: Box<Self>) -> Vec<u8> {
+        Sha256::finish(&mut self).to_vec()
+    }
+}
+
+/// Returns a hasher for the algorithm called `name`, e.g. `"sha256"`, or
+/// `None` if it isn't supported.
+pub fn hasher_for(name: &str) -> Option<Box<DynHasher>> {
+    match name {
+        "sha256" => Some(Box::new(Sha256::new())),
+        _ => None,
+    }
+}
+
+impl Write for Sha256 {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.update(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::{self, Write};
+
+    use hex;
+    use tempfile;
+
+    use super::{hasher_for, verify_file_sha256, Digest256, Sha256};
+
+    #[test]
+    fn digest_of_empty_input() {
+        assert_eq!(
+            hex::encode(Sha256::new().digest()),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn reset_discards_input() {
+        let mut hasher = Sha256::new();
+        hasher.update(b"abc");
+        hasher.reset();
+        hasher.update(b"");
+        assert_eq!(
+            hex::encode(hasher.finish()),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn clone_forks_state() {
+        let mut a = Sha256::new();
+        a.update(b"a");
+        let mut b = a.clone();
+        a.update(b"bc");
+        b.update(b"bd");
+        let a = hex::encode(a.finish());
+        let b = hex::encode(b.finish());
+        assert_eq!(
+            a,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_ne!(a, b);
+
+        let mut expected = Sha256::new();
+        expected.update(b"abd");
+        assert_eq!(b, hex::encode(expected.finish()));
+    }
+
+    #[test]
+    fn clone_after_finish() {
+        let mut a = Sha256::new();
+        a.update(b"xyz");
+        a.finish();
+        a.update(b"abc");
+        assert_eq!(
+            hex::encode(a.clone().finish()),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fixture");
+        File::create(&path).unwrap().write_all(b"abc").unwrap();
+        assert_eq!(
+            hex::encode(Sha256::file(&path).unwrap()),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        let missing = dir.path().join("missing");
+        let err = Sha256::file(&missing).unwrap_err();
+        assert!(err.to_string().starts_with("failed to open `"));
+    }
+
+    #[test]
+    fn finish_hex() {
+        let mut hasher = Sha256::new();
+        hasher.update(b"abc");
+        assert_eq!(
+            hasher.finish_hex(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn hasher_for_name() {
+        let mut hasher = hasher_for("sha256").unwrap();
+        hasher.update(b"abc");
+        let mut expected = Sha256::new();
+        expected.update(b"abc");
+        assert_eq!(hasher.finish(), expected.finish().to_vec());
+
+        assert!(hasher_for("sha512").is_none());
+        assert!(hasher_for("md5").is_none());
+    }
+
+    #[test]
+    fn digest_ct_eq() {
+        let mut hasher = Sha256::new();
+        hasher.update(b"abc");
+        let a = hasher.finish_digest();
+        hasher.reset();
+        hasher.update(b"abc");
+        let b = Digest256::from(hasher.finish());
+        assert!(a.ct_eq(&b));
+
+        let mut other = b.0;
+        other[31] ^= 1;
+        assert!(!a.ct_eq(&Digest256::from(other)));
+        assert!(!a.ct_eq(&Sha256::new().finish_digest()));
+    }
+
+    #[test]
+    fn io_copy() {
+        let data = (0..100_000u32).map(|i| i as u8).collect::<Vec<_>>();
+        let mut chunked = Sha256::new();
+        for chunk in data.chunks(1000) {
+            chunked.update(chunk);
+        }
+
+        let mut copied = Sha256::new();
+        let n = io::copy(&mut &data[..], &mut copied).unwrap();
+        assert_eq!(n, data.len() as u64);
+        copied.flush().unwrap();
+        assert_eq!(copied.finish(), chunked.finish());
+    }
+
+    #[test]
+    fn verify_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fixture");
+        File::create(&path).unwrap().write_all(b"abc").unwrap();
+        let mut expected = Sha256::new();
+        expected.update(b"abc");
+        let expected = expected.finish();
+        verify_file_sha256(&path, &expected).unwrap();
+
+        let err = verify_file_sha256(&path, &[0; 32]).unwrap_err().to_string();
+        assert!(err.starts_with("checksum mismatch for `"), "{}", err);
+        assert!(err.contains(&format!("expected: {}\n", hex::encode([0; 32]))));
+        assert!(err.ends_with(&format!("actual:   {}", hex::encode(expected))));
+    }
+}
@@ -0,0 +1,239 @@
+use std::fmt;
+use std::iter;
+use std::str::{self, FromStr};
+
+use util::{CargoError, CargoResult};
+
+/// A parsed SPDX license expression, such as `MIT OR Apache-2.0`.
+///
+/// License identifiers are only checked to be well formed, not looked up in
+/// the SPDX license list. The legacy `/` separator is accepted as `OR`.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum SpdxExpr {
+    /// A license identifier, optionally with a `WITH` exception.
+    License {
+        id: String,
+        exception: Option<String>,
+    },
+    And(Vec<SpdxExpr>),
+    Or(Vec<SpdxExpr>),
+}
+
+#[derive(PartialEq)]
+enum Token<'a> {
+    LeftParen,
+    RightParen,
+    Slash,
+    Ident(&'a str),
+}
+
+struct Tokenizer<'a> {
+    s: iter::Peekable<str::CharIndices<'a>>,
+    orig: &'a str,
+}
+
+struct Parser<'a> {
+    t: iter::Peekable<Tokenizer<'a>>,
+}
+
+impl SpdxExpr {
+    /// Whether the licenses in `allowed` are enough to satisfy this
+    /// expression, that is every license of an `AND` and at least one
+    /// license of an `OR`.
+    ///
+    /// An exception only ever grants additional permissions, so a license
+    /// with an exception is allowed whenever the license itself is.
+    pub fn allows(&self, allowed: &[&str]) -> bool {
+        match *self {
+            SpdxExpr::License { ref id, .. } => allowed.contains(&&id[..]),
+            SpdxExpr::And(ref e) => e.iter().all(|e| e.allows(allowed)),
+            SpdxExpr::Or(ref e) => e.iter().any(|e| e.allows(allowed)),
+        }
+    }
+}
+
+impl FromStr for SpdxExpr {
+    type Err = CargoError;
+
+    fn from_str(s: &str) -> CargoResult<SpdxExpr> {
+        let mut p = Parser::new(s);
+        let e = (|| -> CargoResult<SpdxExpr> {
+            let e = p.or_expr()?;
+            match p.t.next() {
+                Some(Ok(t)) => bail!(
+                    "expected `AND`, `OR` or the end of the expression, found {}",
+                    t.classify()
+                ),
+                Some(Err(e)) => Err(e),
+                None => Ok(e),
+            }
+        })();
+        e.map_err(|e| format_err!("invalid SPDX license expression `{}`: {}", s, e))
+    }
+}
+
+impl fmt::Display for SpdxExpr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SpdxExpr::License {
+                ref id,
+                exception: Some(ref exception),
+            } => write!(f, "{} WITH {}", id, exception),
+            SpdxExpr::License { ref id, .. } => id.fmt(f),
+            SpdxExpr::And(ref e) => {
+                for (i, e) in e.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " AND ")?;
+                    }
+                    match *e {
+                        SpdxExpr::Or(..) => write!(f, "({})", e)?,
+                        _ => write!(f, "{}", e)?,
+                    }
+                }
+                Ok(())
+            }
+            SpdxExpr::Or(ref e) => {
+                for (i, e) in e.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " OR ")?;
+                    }
+                    write!(f, "{}", e)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Parser<'a> {
+        Parser {
+            t: Tokenizer {
+                s: s.char_indices().peekable(),
+                orig: s,
+            }.peekable(),
+        }
+    }
+
+    fn or_expr(&mut self) -> CargoResult<SpdxExpr> {
+        let mut e = vec![self.and_expr()?];
+        while self.try(Token::Ident("OR")) || self.try(Token::Slash) {
+            e.push(self.and_expr()?);
+        }
+        if e.len() == 1 {
+            Ok(e.pop().unwrap())
+        } else {
+            Ok(SpdxExpr::Or(e))
+        }
+    }
+
+    fn and_expr(&mut self) -> CargoResult<SpdxExpr> {
+        let mut e = vec![self.license()?];
+        while self.try(Token::Ident("AND")) {
+            e.push(self.license()?);
+        }
+        if e.len() == 1 {
+            Ok(e.pop().unwrap())
+        } else {
+            Ok(SpdxExpr::And(e))
+        }
+    }
+
+    fn license(&mut self) -> CargoResult<SpdxExpr> {
+        if self.try(Token::LeftParen) {
+            let e = self.or_expr()?;
+            self.eat(Token::RightParen)?;
+            return Ok(e);
+        }
+        let id = self.ident()?;
+        let exception = if self.try(Token::Ident("WITH")) {
+            Some(self.ident()?.to_string())
+        } else {
+            None
+        };
+        Ok(SpdxExpr::License {
+            id: id.to_string(),
+            exception,
+        })
+    }
+
+    fn ident(&mut self) -> CargoResult<&'a str> {
+        match self.t.next() {
+            Some(Ok(Token::Ident(op @ "AND")))
+            | Some(Ok(Token::Ident(op @ "OR")))
+            | Some(Ok(Token::Ident(op @ "WITH"))) => {
+                bail!("expected a license identifier, found `{}`", op)
+            }
+            Some(Ok(Token::Ident(id))) => Ok(id),
+            Some(Ok(t)) => bail!("expected a license identifier, found {}", t.classify()),
+            Some(Err(e)) => Err(e),
+            None => bail!("expected a license identifier, found nothing"),
+        }
+    }
+
+    fn try(&mut self, token: Token<'a>) -> bool {
+        match self.t.peek() {
+            Some(&Ok(ref t)) if token == *t => {}
+            _ => return false,
+        }
+        self.t.next();
+        true
+    }
+
+    fn eat(&mut self, token: Token<'a>) -> CargoResult<()> {
+        match self.t.next() {
+            Some(Ok(ref t)) if token == *t => Ok(()),
+            Some(Ok(t)) => bail!("expected {}, found {}", token.classify(), t.classify()),
+            Some(Err(e)) => Err(e),
+            None => bail!("expected {}, but the expression ended", token.classify()),
+        }
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = CargoResult<Token<'a>>;
+
+    fn next(&mut self) -> Option<CargoResult<Token<'a>>> {
+        loop {
+            match self.s.next() {
+                Some((_, ' ')) => {}
+                Some((_, '(')) => return Some(Ok(Token::LeftParen)),
+                Some((_, ')')) => return Some(Ok(Token::RightParen)),
+                Some((_, '/')) => return Some(Ok(Token::Slash)),
+                Some((start, ch)) if is_ident(ch) => {
+                    while let Some(&(end, ch)) = self.s.peek() {
+                        if !is_ident(ch) {
+                            return Some(Ok(Token::Ident(&self.orig[start..end])));
+                        } else {
+                            self.s.next();
+                        }
+                    }
+                    return Some(Ok(Token::Ident(&self.orig[start..])));
+                }
+                Some((_, ch)) => {
+                    return Some(Err(format_err!(
+                        "unexpected character `{}`, expected parens, \
+                         `/`, or a license identifier",
+                        ch
+                    )))
+                }
+                None => return None,
+            }
+        }
+    }
+}
+
+fn is_ident(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '-' || ch == '.' || ch == '+' || ch == ':'
+}
+
+impl<'a> Token<'a> {
+    fn classify(&self) -> &str {
+        match *self {
+            Token::LeftParen => "`(`",
+            Token::RightParen => "`)`",
+            Token::Slash => "`/`",
+            Token::Ident(..) => "a license identifier",
+        }
+    }
+}
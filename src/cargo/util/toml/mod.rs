@@ -13,18 +13,18 @@ use toml;
 use url::Url;
 
 use core::dependency::{Kind, Platform};
-use core::manifest::{LibKind, ManifestMetadata, Warnings};
+use core::manifest::{DefaultRun, LibKind, ManifestMetadata, RequiredFeatures, Warnings};
 use core::profiles::Profiles;
 use core::{Dependency, Manifest, PackageId, Summary, Target};
 use core::{Edition, EitherManifest, Feature, Features, VirtualManifest};
 use core::{GitReference, PackageIdSpec, SourceId, WorkspaceConfig, WorkspaceRootConfig};
 use sources::CRATES_IO;
-use util::errors::{CargoError, CargoResult, CargoResultExt};
+use util::errors::{internal, CargoError, CargoResult, CargoResultExt};
 use util::paths;
 use util::{self, Config, ToUrl};
 
 mod targets;
-use self::targets::targets;
+use self::targets::{autodiscovers, targets};
 
 pub fn read_manifest(
     path: &Path,
@@ -565,6 +565,54 @@ impl<'de> de::Deserialize<'de> for VecStringOrBool {
     }
 }
 
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum TomlDefaultRun {
+    Bin(String),
+    Candidates(Vec<TomlDefaultRunCandidate>),
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct TomlDefaultRunCandidate {
+    bin: String,
+    #[serde(rename = "required-features")]
+    required_features: Option<Vec<String>>,
+}
+
+impl<'de> de::Deserialize<'de> for TomlDefaultRun {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = TomlDefaultRun;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a string or a list of binaries")
+            }
+
+            fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(TomlDefaultRun::Bin(s.to_string()))
+            }
+
+            fn visit_seq<V>(self, v: V) -> Result<Self::Value, V::Error>
+            where
+                V: de::SeqAccess<'de>,
+            {
+                let seq = de::value::SeqAccessDeserializer::new(v);
+                Vec::deserialize(seq).map(TomlDefaultRun::Candidates)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct TomlProject {
     name: String,
@@ -587,7 +635,9 @@ pub struct TomlProject {
     #[serde(rename = "namespaced-features")]
     namespaced_features: Option<bool>,
     #[serde(rename = "default-run")]
-    default_run: Option<String>,
+    default_run: Option<TomlDefaultRun>,
+    #[serde(rename = "rust-version")]
+    rust_version: Option<VersionReq>,
 
     // package metadata
     description: Option<String>,
@@ -610,9 +660,95 @@ pub struct TomlWorkspace {
     #[serde(rename = "default-members")]
     default_members: Option<Vec<String>>,
     exclude: Option<Vec<String>>,
+    package: Option<TomlWorkspacePackage>,
+}
+
+/// The `[workspace.package]` table, metadata which members inherit unless
+/// they set it themselves.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TomlWorkspacePackage {
+    authors: Option<Vec<String>>,
+    keywords: Option<Vec<String>>,
+    categories: Option<Vec<String>>,
+    license: Option<String>,
+    #[serde(rename = "license-file")]
+    license_file: Option<String>,
+    homepage: Option<String>,
+    repository: Option<String>,
+    documentation: Option<String>,
+}
+
+impl TomlWorkspace {
+    fn to_workspace_config(
+        &self,
+        root: &Path,
+        features: &Features,
+    ) -> CargoResult<WorkspaceConfig> {
+        let package = match self.package {
+            Some(ref package) => {
+                features.require(Feature::workspace_inheritance())?;
+                Some(ManifestMetadata {
+                    authors: package.authors.clone().unwrap_or_default(),
+                    keywords: package.keywords.clone().unwrap_or_default(),
+                    categories: package.categories.clone().unwrap_or_default(),
+                    license: package.license.clone(),
+                    license_file: package.license_file.clone(),
+                    description: None,
+                    readme: None,
+                    homepage: package.homepage.clone(),
+                    repository: package.repository.clone(),
+                    documentation: package.documentation.clone(),
+                    badges: BTreeMap::new(),
+                    links: None,
+                })
+            }
+            None => None,
+        };
+        Ok(WorkspaceConfig::Root(WorkspaceRootConfig::new(
+            root,
+            &self.members,
+            &self.default_members,
+            &self.exclude,
+            package,
+        )))
+    }
 }
 
 impl TomlProject {
+    /// Fills in the metadata this package leaves unset from its workspace's
+    /// `[workspace.package]` table, see `ManifestMetadata::inherit`.
+    fn inherit_metadata(&mut self, workspace: &ManifestMetadata) {
+        let own = ManifestMetadata {
+            authors: self.authors.clone().unwrap_or_default(),
+            keywords: self.keywords.clone().unwrap_or_default(),
+            categories: self.categories.clone().unwrap_or_default(),
+            license: self.license.clone(),
+            license_file: self.license_file.clone(),
+            description: None,
+            readme: None,
+            homepage: self.homepage.clone(),
+            repository: self.repository.clone(),
+            documentation: self.documentation.clone(),
+            badges: BTreeMap::new(),
+            links: None,
+        };
+        let merged = own.inherit(workspace);
+        if !merged.authors.is_empty() {
+            self.authors = Some(merged.authors);
+        }
+        if !merged.keywords.is_empty() {
+            self.keywords = Some(merged.keywords);
+        }
+        if !merged.categories.is_empty() {
+            self.categories = Some(merged.categories);
+        }
+        self.license = merged.license;
+        self.license_file = merged.license_file;
+        self.homepage = merged.homepage;
+        self.repository = merged.repository;
+        self.documentation = merged.documentation;
+    }
+
     pub fn to_package_id(&self, source_id: &SourceId) -> CargoResult<PackageId> {
         PackageId::new(&self.name, self.version.clone(), source_id)
     }
@@ -631,6 +767,15 @@ struct Context<'a, 'b> {
 }
 
 impl TomlManifest {
+    /// Fills in the metadata the package leaves unset from `metadata`, its
+    /// resolved metadata, so that a normalized manifest carries what the
+    /// package inherited from its workspace's `[workspace.package]` table.
+    pub fn inherit_metadata(&mut self, metadata: &ManifestMetadata) {
+        if let Some(project) = self.package.as_mut().or(self.project.as_mut()) {
+            project.inherit_metadata(metadata);
+        }
+    }
+
     pub fn prepare_for_publish(&self, config: &Config) -> CargoResult<TomlManifest> {
         let mut package = self
             .package
@@ -918,12 +1063,7 @@ impl TomlManifest {
         };
 
         let workspace_config = match (me.workspace.as_ref(), project.workspace.as_ref()) {
-            (Some(config), None) => WorkspaceConfig::Root(WorkspaceRootConfig::new(
-                &package_root,
-                &config.members,
-                &config.default_members,
-                &config.exclude,
-            )),
+            (Some(config), None) => config.to_workspace_config(&package_root, &features)?,
             (None, root) => WorkspaceConfig::Member {
                 root: root.cloned(),
             },
@@ -954,6 +1094,19 @@ impl TomlManifest {
             None => false,
         };
 
+        let default_run = project.default_run.as_ref().map(|default_run| match *default_run {
+            TomlDefaultRun::Bin(ref bin) => DefaultRun::Bin(bin.clone()),
+            TomlDefaultRun::Candidates(ref candidates) => DefaultRun::Candidates(
+                candidates
+                    .iter()
+                    .map(|c| {
+                        let features = c.required_features.as_ref().map(|f| &f[..]);
+                        (c.bin.clone(), RequiredFeatures::new(features.unwrap_or(&[])))
+                    })
+                    .collect(),
+            ),
+        });
+
         let custom_metadata = project.metadata.clone();
         let mut manifest = Manifest::new(
             summary,
@@ -971,10 +1124,15 @@ impl TomlManifest {
             workspace_config,
             features,
             edition,
+            autodiscovers(me.bin.as_ref(), project.autobins, edition),
+            autodiscovers(me.example.as_ref(), project.autoexamples, edition),
+            autodiscovers(me.test.as_ref(), project.autotests, edition),
+            autodiscovers(me.bench.as_ref(), project.autobenches, edition),
             project.im_a_teapot,
-            project.default_run.clone(),
+            default_run,
+            project.rust_version.clone(),
             Rc::clone(me),
-        );
+        )?;
         if project.license_file.is_some() && project.license.is_some() {
             manifest.warnings_mut().add_warning(
                 "only one of `license` or \
@@ -991,6 +1149,23 @@ impl TomlManifest {
 
         manifest.feature_gate()?;
 
+        if let Some(ref req) = project.rust_version {
+            // Failing to query rustc here isn't fatal, the build itself will
+            // report that soon enough.
+            if let Ok(version) = rustc_release(config) {
+                if !req.matches(&version) {
+                    let msg = format!(
+                        "package `{}` requires rustc {}, but the current \
+                         compiler is {}",
+                        manifest.package_id(),
+                        req,
+                        version
+                    );
+                    manifest.warnings_mut().add_warning(msg);
+                }
+            }
+        }
+
         Ok((manifest, nested_paths))
     }
 
@@ -1045,12 +1220,7 @@ impl TomlManifest {
         };
         let profiles = Profiles::new(me.profile.as_ref(), config, &features, &mut warnings)?;
         let workspace_config = match me.workspace {
-            Some(ref config) => WorkspaceConfig::Root(WorkspaceRootConfig::new(
-                &root,
-                &config.members,
-                &config.default_members,
-                &config.exclude,
-            )),
+            Some(ref config) => config.to_workspace_config(root, &features)?,
             None => {
                 bail!("virtual manifests must be configured with [workspace]");
             }
@@ -1420,3 +1590,19 @@ impl fmt::Debug for PathValue {
         self.0.fmt(f)
     }
 }
+
+/// Returns the release of the `rustc` cargo is configured to use, with any
+/// pre-release tag such as `-nightly` stripped so that it can be matched
+/// against a `rust-version` requirement.
+fn rustc_release(config: &Config) -> CargoResult<semver::Version> {
+    let rustc = config.rustc(None)?;
+    let release = rustc
+        .verbose_version
+        .lines()
+        .find(|l| l.starts_with("release: "))
+        .map(|l| &l[9..])
+        .ok_or_else(|| internal("rustc -v didn't have a line for `release:`"))?;
+    let mut version = semver::Version::parse(release)?;
+    version.pre.clear();
+    Ok(version)
+}
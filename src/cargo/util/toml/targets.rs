@@ -104,6 +104,10 @@ pub fn targets(
         ));
     }
 
+    for target in targets.iter_mut() {
+        target.set_edition(edition);
+    }
+
     Ok(targets)
 }
 
@@ -553,6 +557,20 @@ fn is_not_dotfile(entry: &DirEntry) -> bool {
     entry.file_name().to_str().map(|s| s.starts_with('.')) == Some(false)
 }
 
+/// Whether the targets inferred from the directory layout are used next to
+/// `toml_targets`. Without any explicit sections they always are, otherwise
+/// it's up to the `auto*` flag, which defaults to on starting with 2018.
+pub fn autodiscovers(
+    toml_targets: Option<&Vec<TomlTarget>>,
+    autodiscover: Option<bool>,
+    edition: Edition,
+) -> bool {
+    match toml_targets {
+        None => true,
+        Some(_) => autodiscover.unwrap_or(edition >= Edition::Edition2018),
+    }
+}
+
 fn toml_targets_and_inferred(
     toml_targets: Option<&Vec<TomlTarget>>,
     inferred: &[(String, PathBuf)],
@@ -565,6 +583,7 @@ fn toml_targets_and_inferred(
     autodiscover_flag_name: &str,
 ) -> Vec<TomlTarget> {
     let inferred_targets = inferred_to_toml_targets(inferred);
+    let discover = autodiscovers(toml_targets, autodiscover, edition);
     match toml_targets {
         None => inferred_targets,
         Some(targets) => {
@@ -588,20 +607,16 @@ fn toml_targets_and_inferred(
                 }
             }
 
-            let autodiscover = match autodiscover {
-                Some(autodiscover) => autodiscover,
-                None => match edition {
-                    Edition::Edition2018 => true,
-                    Edition::Edition2015 => {
-                        if !rem_targets.is_empty() {
-                            let mut rem_targets_str = String::new();
-                            for t in rem_targets.iter() {
-                                if let Some(p) = t.path.clone() {
-                                    rem_targets_str.push_str(&format!("* {}\n", p.0.display()))
-                                }
-                            }
-                            warnings.push(format!(
-                                "\
+            // Only warn when the edition's default left these out.
+            if autodiscover.is_none() && !discover && !rem_targets.is_empty() {
+                let mut rem_targets_str = String::new();
+                for t in rem_targets.iter() {
+                    if let Some(p) = t.path.clone() {
+                        rem_targets_str.push_str(&format!("* {}\n", p.0.display()))
+                    }
+                }
+                warnings.push(format!(
+                    "\
 An explicit [[{section}]] section is specified in Cargo.toml which currently
 disables Cargo from automatically inferring other {target_kind_human} targets.
 This inference behavior will change in the Rust 2018 edition and the following
@@ -616,18 +631,14 @@ automatically infer them to be a target, such as in subfolders.
 
 For more information on this warning you can consult
 https://github.com/rust-lang/cargo/issues/5330",
-                                section = target_kind,
-                                target_kind_human = target_kind_human,
-                                rem_targets_str = rem_targets_str,
-                                autodiscover_flag_name = autodiscover_flag_name,
-                            ));
-                        };
-                        false
-                    }
-                },
-            };
+                    section = target_kind,
+                    target_kind_human = target_kind_human,
+                    rem_targets_str = rem_targets_str,
+                    autodiscover_flag_name = autodiscover_flag_name,
+                ));
+            }
 
-            if autodiscover {
+            if discover {
                 targets.append(&mut rem_targets);
             }
 
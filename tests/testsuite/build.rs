@@ -5666,3 +5666,33 @@ fn invalid_jobs() {
             .with_stderr("error: Invalid value: could not parse `over9000` as a number"),
     );
 }
+
+#[test]
+fn rust_version_newer_than_rustc() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["rust-version"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            rust-version = ">= 99.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build").masquerade_as_nightly_cargo(),
+        execs().with_status(0).with_stderr(
+            "\
+[WARNING] package `foo v0.0.1 ([..])` requires rustc [..], but the current compiler is [..]
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        ),
+    );
+}
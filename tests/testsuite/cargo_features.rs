@@ -54,6 +54,45 @@ switch to nightly channel you can add
     );
 }
 
+#[test]
+fn multiple_features_required() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "a"
+            version = "0.0.1"
+            authors = []
+            im-a-teapot = true
+            default-run = "a"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    assert_that(
+        p.cargo("build").masquerade_as_nightly_cargo(),
+        execs().with_status(101).with_stderr(
+            "\
+error: failed to parse manifest at `[..]`
+
+Caused by:
+  multiple unstable manifest keys are used
+
+the `im-a-teapot` manifest key is unstable and may not work properly in England
+feature `test-dummy-unstable` is required
+
+consider adding `cargo-features = [\"test-dummy-unstable\"]` to the manifest
+
+the `default-run` manifest key is unstable
+feature `default-run` is required
+
+consider adding `cargo-features = [\"default-run\"]` to the manifest
+",
+        ),
+    );
+}
+
 #[test]
 fn unknown_feature() {
     let p = project("foo")
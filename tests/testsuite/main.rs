@@ -60,6 +60,7 @@ mod jobserver;
 mod local_registry;
 mod lockfile_compat;
 mod login;
+mod manifest;
 mod metadata;
 mod net_config;
 mod new;
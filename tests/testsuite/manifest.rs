@@ -0,0 +1,1152 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use cargo::core::compiler::CompileMode;
+use cargo::core::manifest::{check_links_collisions, EntryDiff};
+use cargo::core::{enable_nightly_features, Dependency, Edition, EitherManifest, InternedString,
+                  Manifest, PackageId, SourceId, Target, TargetKind, WorkspaceConfig};
+use cargo::util::toml::read_manifest;
+use cargo::util::Config;
+
+use cargotest::support::{execs, project, Project};
+use hamcrest::assert_that;
+
+/// Reads the manifest at the root of `p`, which may be a virtual one.
+fn either_manifest(p: &Project) -> EitherManifest {
+    let path = p.root().join("Cargo.toml");
+    let source_id = SourceId::for_path(&p.root()).unwrap();
+    let config = Config::default().unwrap();
+    read_manifest(&path, &source_id, &config).unwrap().0
+}
+
+/// Reads the manifest of the package at the root of `p`.
+fn manifest(p: &Project) -> Manifest {
+    match either_manifest(p) {
+        EitherManifest::Real(manifest) => manifest,
+        EitherManifest::Virtual(_) => panic!("expected a real manifest"),
+    }
+}
+
+#[test]
+fn path_exclusion() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+            exclude = ["*.txt", "/target"]
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let m = manifest(&p);
+    assert!(m.is_path_excluded(Path::new("notes.txt")));
+    assert!(m.is_path_excluded(Path::new("target")));
+    assert!(!m.is_path_excluded(Path::new("src/lib.rs")));
+
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+            include = ["src/*", "Cargo.toml"]
+            exclude = ["src/lib.rs"]
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let m = manifest(&p);
+    assert!(!m.is_path_excluded(Path::new("src/lib.rs")));
+    assert!(!m.is_path_excluded(Path::new("Cargo.toml")));
+    assert!(m.is_path_excluded(Path::new("README.md")));
+}
+
+#[test]
+fn invalid_exclude_pattern() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+            exclude = ["src/["]
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    assert_that(
+        p.cargo("build"),
+        execs().with_status(101).with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  could not parse glob pattern `src/[`: [..]
+",
+        ),
+    );
+}
+
+#[test]
+fn links_collisions() {
+    let package = |name: &str| {
+        project(name)
+            .file(
+                "Cargo.toml",
+                &format!(
+                    r#"
+                    [package]
+                    name = "{}"
+                    version = "0.1.0"
+                    authors = []
+                    links = "foo"
+                "#,
+                    name
+                ),
+            )
+            .file("src/lib.rs", "")
+            .build()
+    };
+    let a = manifest(&package("a"));
+    let b = manifest(&package("b"));
+    assert!(check_links_collisions(&[&a]).is_ok());
+
+    let err = check_links_collisions(&[&a, &b]).unwrap_err().to_string();
+    assert!(err.contains("native library `foo`"), "{}", err);
+    assert!(err.contains("`a v0.1.0"), "{}", err);
+    assert!(err.contains("`b v0.1.0"), "{}", err);
+}
+
+#[test]
+fn custom_metadata_section() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Deb {
+        section: String,
+    }
+
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [package.metadata.deb]
+            section = "utils"
+
+            [package.metadata.bad]
+            section = 1
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let m = manifest(&p);
+    assert_eq!(
+        m.custom_metadata_section::<Deb>("deb").unwrap(),
+        Some(Deb {
+            section: "utils".to_string()
+        })
+    );
+    assert_eq!(m.custom_metadata_section::<Deb>("missing").unwrap(), None);
+    let err = m.custom_metadata_section::<Deb>("bad").unwrap_err();
+    assert_eq!(err.to_string(), "failed to parse `package.metadata.bad`");
+}
+
+#[test]
+fn target_profile_overrides() {
+    enable_nightly_features();
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["profile-overrides"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [profile.dev]
+            opt-level = 1
+
+            [profile.dev.overrides.foo]
+            opt-level = 3
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let m = manifest(&p);
+    let lib = m.targets().iter().find(|t| t.is_lib()).unwrap();
+    let profile = m.target_profile(m.package_id(), true, lib, CompileMode::Build, false);
+    assert_eq!(&*profile.opt_level, "3");
+    let bar = PackageId::new("bar", "0.1.0", m.package_id().source_id()).unwrap();
+    let profile = m.target_profile(&bar, true, lib, CompileMode::Build, false);
+    assert_eq!(&*profile.opt_level, "1");
+}
+
+#[test]
+fn patches_for_source() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [patch.crates-io]
+            bar = { path = "bar" }
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let m = manifest(&p);
+    let crates_io = SourceId::crates_io(&Config::default().unwrap()).unwrap();
+    let patches = m.patches_for(&crates_io);
+    assert_eq!(patches.len(), 1);
+    assert_eq!(&*patches[0].name(), "bar");
+
+    let other = SourceId::for_path(&p.root()).unwrap();
+    assert!(m.patches_for(&other).is_empty());
+}
+
+#[test]
+fn publish_registries() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let any = manifest(&p);
+    assert!(any.can_publish_to("alternative"));
+    assert!(!any.publish_forbidden());
+
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            publish = false
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let never = manifest(&p);
+    assert!(!never.can_publish_to("alternative"));
+    assert!(never.publish_forbidden());
+
+    enable_nightly_features();
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["alternative-registries"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            publish = ["alternative"]
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let listed = manifest(&p);
+    assert!(listed.can_publish_to("alternative"));
+    assert!(!listed.can_publish_to("other"));
+    assert!(!listed.publish_forbidden());
+}
+
+#[test]
+fn edition_at_least() {
+    assert!(Edition::Edition2015 < Edition::Edition2018);
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let m = manifest(&p);
+    assert!(m.edition_at_least(Edition::Edition2015));
+    assert!(!m.edition_at_least(Edition::Edition2018));
+
+    enable_nightly_features();
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["edition"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2018"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let m = manifest(&p);
+    assert!(m.edition_at_least(Edition::Edition2015));
+    assert!(m.edition_at_least(Edition::Edition2018));
+}
+
+#[test]
+fn workspace_members() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [workspace]
+            members = ["a", "b"]
+            default-members = ["a"]
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let root = manifest(&p);
+    assert_eq!(
+        root.workspace_members(),
+        Some(&["a".to_string(), "b".to_string()][..])
+    );
+    assert_eq!(
+        root.workspace_default_members(),
+        Some(&["a".to_string()][..])
+    );
+
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            workspace = ".."
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let member = manifest(&p);
+    assert_eq!(member.workspace_members(), None);
+    assert_eq!(member.workspace_default_members(), None);
+}
+
+#[test]
+fn custom_build_target() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let m = manifest(&p);
+    assert!(!m.has_custom_build());
+    assert!(m.custom_build_target().is_none());
+
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            build = "build.rs"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .build();
+    let m = manifest(&p);
+    assert!(m.has_custom_build());
+    let build = m.custom_build_target().unwrap();
+    assert!(build.is_custom_build());
+    assert_eq!(build.name(), "build-script-build");
+}
+
+#[test]
+fn targets_of_kind() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            build = "build.rs"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "")
+        .file("src/bin/other.rs", "")
+        .file("examples/ex.rs", "")
+        .file("tests/t.rs", "")
+        .file("benches/b.rs", "")
+        .file("build.rs", "")
+        .build();
+    let m = manifest(&p);
+    let names = |targets: Vec<&Target>| {
+        let mut names = targets.iter().map(|t| t.name().to_string()).collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+    assert_eq!(names(m.bin_targets().collect()), vec!["foo", "other"]);
+    assert_eq!(m.lib_target().map(|t| t.name()), Some("foo"));
+    assert_eq!(names(m.example_targets().collect()), vec!["ex"]);
+    assert_eq!(names(m.test_targets().collect()), vec!["t"]);
+    assert_eq!(names(m.bench_targets().collect()), vec!["b"]);
+    assert_eq!(
+        names(m.targets_of_kind(|k| *k == TargetKind::CustomBuild).collect()),
+        vec!["build-script-build"]
+    );
+
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+        "#,
+        )
+        .file("src/main.rs", "")
+        .build();
+    assert!(manifest(&p).lib_target().is_none());
+}
+
+#[test]
+fn validate_target_uniqueness() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "")
+        .file("examples/foo.rs", "")
+        .build();
+    assert!(manifest(&p).validate_target_uniqueness().is_ok());
+}
+
+#[test]
+fn canonical_bytes() {
+    let p = project("a")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = "1.0"
+            baz = { version = "0.2", optional = true }
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let a = manifest(&p);
+    let p = project("b")
+        .file(
+            "Cargo.toml",
+            r#"
+            [dependencies]
+            baz = { optional = true, version = "0.2" }
+            bar = "1.0"
+
+            [package]
+            version = "0.1.0"
+            name = "foo"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let b = manifest(&p);
+    let bytes = a.canonical_bytes().unwrap();
+    assert_eq!(bytes, a.canonical_bytes().unwrap());
+    assert_eq!(bytes, b.canonical_bytes().unwrap());
+}
+
+#[test]
+fn into_strict() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    assert!(manifest(&p).into_strict().is_ok());
+
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            unknown-a = 1
+            unknown-b = 2
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    assert_eq!(
+        manifest(&p).into_strict().unwrap_err().to_string(),
+        "manifest for `foo` has 2 warning(s), which are denied:\n  \
+         unused manifest key: package.unknown-a\n  \
+         unused manifest key: package.unknown-b"
+    );
+}
+
+#[test]
+fn replacement_for() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [replace]
+            "bar:1.0.0" = { git = "https://example.com/bar" }
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let m = manifest(&p);
+    let crates_io = SourceId::crates_io(&Config::default().unwrap()).unwrap();
+
+    let bar = PackageId::new("bar", "1.0.0", &crates_io).unwrap();
+    let dep = m.replacement_for(&bar).unwrap();
+    assert_eq!(&*dep.name(), "bar");
+    assert!(dep.source_id().is_git());
+
+    let other_version = PackageId::new("bar", "1.0.1", &crates_io).unwrap();
+    assert!(m.replacement_for(&other_version).is_none());
+    let other_name = PackageId::new("baz", "1.0.0", &crates_io).unwrap();
+    assert!(m.replacement_for(&other_name).is_none());
+}
+
+#[test]
+fn declared_features() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = { version = "1.0", optional = true }
+
+            [features]
+            default = ["a"]
+            a = ["b", "bar/x"]
+            b = []
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let m = manifest(&p);
+    let features = m.declared_features();
+    let values = |name: &str| {
+        features[&InternedString::new(name)]
+            .iter()
+            .map(|v| v.to_string(m.summary()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        features.keys().map(|k| k.to_string()).collect::<Vec<_>>(),
+        vec!["a", "b", "default"]
+    );
+    assert_eq!(values("default"), vec!["a"]);
+    assert_eq!(values("a"), vec!["b", "bar/x"]);
+    assert!(values("b").is_empty());
+}
+
+#[test]
+fn duplicate_target_names() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "")
+        .file("examples/foo.rs", "")
+        .build();
+    assert!(manifest(&p).duplicate_target_names().is_empty());
+}
+
+#[test]
+fn dependencies_by_kind() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            a = "1.0"
+            b = "1.0"
+
+            [target.'cfg(unix)'.dependencies]
+            c = "1.0"
+
+            [dev-dependencies]
+            d = "1.0"
+
+            [build-dependencies]
+            e = "1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let m = manifest(&p);
+    let names = |deps: Vec<&Dependency>| {
+        let mut names = deps.iter().map(|d| d.name().to_string()).collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+    assert_eq!(names(m.normal_dependencies().collect()), vec!["a", "b", "c"]);
+    assert_eq!(names(m.dev_dependencies().collect()), vec!["d"]);
+    assert_eq!(names(m.build_dependencies().collect()), vec!["e"]);
+    assert_eq!(m.dependencies().len(), 5);
+}
+
+#[test]
+fn default_runnable_target() {
+    enable_nightly_features();
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["default-run"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            default-run = "a"
+        "#,
+        )
+        .file("src/main.rs", "")
+        .file("src/bin/a.rs", "")
+        .file("src/bin/b.rs", "")
+        .build();
+    let m = manifest(&p);
+    assert_eq!(m.default_runnable_target(&HashSet::new()).unwrap().name(), "a");
+
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+        "#,
+        )
+        .file("src/main.rs", "")
+        .build();
+    let m = manifest(&p);
+    assert_eq!(m.default_runnable_target(&HashSet::new()).unwrap().name(), "foo");
+
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+        "#,
+        )
+        .file("src/main.rs", "")
+        .file("src/bin/a.rs", "")
+        .file("src/bin/b.rs", "")
+        .build();
+    let m = manifest(&p);
+    let err = m.default_runnable_target(&HashSet::new()).unwrap_err().to_string();
+    assert!(err.ends_with("available binaries: a, b, foo"), "{}", err);
+
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["default-run"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            default-run = [{ bin = "a" }, { bin = "foo" }]
+
+            [features]
+            fancy = []
+
+            [[bin]]
+            name = "a"
+            required-features = ["fancy"]
+
+            [[bin]]
+            name = "foo"
+            path = "src/main.rs"
+        "#,
+        )
+        .file("src/main.rs", "")
+        .file("src/bin/a.rs", "")
+        .build();
+    let m = manifest(&p);
+    assert_eq!(m.default_runnable_target(&HashSet::new()).unwrap().name(), "foo");
+    let fancy = vec![InternedString::new("fancy")].into_iter().collect();
+    assert_eq!(m.default_runnable_target(&fancy).unwrap().name(), "a");
+}
+
+#[test]
+fn example_harness() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [[example]]
+            name = "e"
+            harness = false
+            test = true
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file("examples/e.rs", "")
+        .build();
+    let m = manifest(&p);
+    let example = m.example_targets().next().unwrap();
+    assert!(!example.harness());
+    assert!(example.runs_as_custom_test());
+}
+
+#[test]
+fn resolution_fingerprint() {
+    // Each package lives in a directory of its own.
+    let fingerprint = |dir: &str, package: &str, dependencies: &str| {
+        let p = project(dir)
+            .file(
+                "Cargo.toml",
+                &format!(
+                    r#"
+                    [package]
+                    name = "foo"
+                    version = "0.1.0"
+                    {}
+
+                    [dependencies]
+                    bar = {{ version = "1.0", optional = true }}
+                    {}
+
+                    [features]
+                    default = ["bar"]
+                "#,
+                    package, dependencies
+                ),
+            )
+            .file("src/lib.rs", "")
+            .build();
+        manifest(&p).resolution_fingerprint(&p.root()).unwrap()
+    };
+    let base = fingerprint("a", "", "");
+    assert_eq!(base, fingerprint("b", "", ""));
+    assert_eq!(
+        base,
+        fingerprint("b", "description = \"changed\"\nauthors = [\"a\"]", "")
+    );
+    assert_ne!(base, fingerprint("b", "", "baz = \"1.0\""));
+    assert_ne!(base, fingerprint("b", "links = \"z\"", ""));
+
+    let path_dep = fingerprint("a", "", "baz = { path = \"../baz\" }");
+    assert_eq!(path_dep, fingerprint("b", "", "baz = { path = \"../baz\" }"));
+    assert_ne!(path_dep, fingerprint("b", "", "baz = { path = \"../qux/baz\" }"));
+}
+
+#[test]
+fn default_member_paths() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [workspace]
+            members = ["a", "b"]
+            default-members = ["a", "crates/c"]
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    assert_eq!(
+        manifest(&p).default_member_paths(&p.root()),
+        Some(vec![p.root().join("a"), p.root().join("crates/c")])
+    );
+
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [workspace]
+            members = ["a"]
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    assert_eq!(manifest(&p).default_member_paths(&p.root()), None);
+}
+
+#[test]
+fn autodiscovery_flags() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            autobins = false
+
+            [[bin]]
+            name = "foo"
+            path = "src/main.rs"
+        "#,
+        )
+        .file("src/main.rs", "")
+        .file("src/bin/other.rs", "")
+        .build();
+    let m = manifest(&p);
+    assert!(!m.autobins());
+    assert!(m.autoexamples());
+    let bins = m.bin_targets().map(|t| t.name()).collect::<Vec<_>>();
+    assert_eq!(bins, vec!["foo"]);
+
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [[example]]
+            name = "ex"
+            path = "examples/ex.rs"
+        "#,
+        )
+        .file("src/main.rs", "")
+        .file("src/bin/other.rs", "")
+        .file("examples/ex.rs", "")
+        .build();
+    let m = manifest(&p);
+    assert!(m.autobins());
+    assert!(!m.autoexamples());
+
+    enable_nightly_features();
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["edition"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            edition = "2018"
+            autobins = false
+
+            [[example]]
+            name = "ex"
+            path = "examples/ex.rs"
+        "#,
+        )
+        .file("src/main.rs", "")
+        .file("src/bin/other.rs", "")
+        .file("examples/ex.rs", "")
+        .file("examples/other.rs", "")
+        .build();
+    let m = manifest(&p);
+    // `autobins` only applies next to `[[bin]]` sections, without any the
+    // inferred binaries are all used.
+    assert!(m.autobins());
+    assert_eq!(m.bin_targets().count(), 2);
+    assert!(m.autoexamples());
+    assert_eq!(m.example_targets().count(), 2);
+    assert!(m.autotests());
+    assert!(m.autobenches());
+}
+
+#[test]
+fn enabled_targets() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [features]
+            a = []
+            b = []
+
+            [[example]]
+            name = "ex"
+            required-features = ["a", "b"]
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file("examples/ex.rs", "")
+        .build();
+    let m = manifest(&p);
+    let enabled = |features: &[&str]| {
+        let features = features.iter().map(|f| InternedString::new(f)).collect();
+        let mut names = m.enabled_targets(&features)
+            .iter()
+            .map(|t| t.name().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+    assert_eq!(enabled(&[]), vec!["foo"]);
+    assert_eq!(enabled(&["a"]), vec!["foo"]);
+    assert_eq!(enabled(&["a", "b"]), vec!["ex", "foo"]);
+}
+
+#[test]
+fn either_manifest_accessors() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [workspace]
+
+            [replace]
+            "bar:1.0.0" = { git = "https://example.com/bar" }
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    let real = either_manifest(&p);
+    assert_eq!(
+        real.as_real().map(|m| m.name().to_string()),
+        Some("foo".to_string())
+    );
+    assert!(real.as_virtual().is_none());
+    match *real.workspace_config() {
+        WorkspaceConfig::Root(..) => {}
+        WorkspaceConfig::Member { .. } => panic!("expected a workspace root"),
+    }
+    assert_eq!(real.replace().len(), 1);
+    assert!(real.patch().is_empty());
+
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["a"]
+            bogus = 1
+
+            [patch.crates-io]
+            bar = { git = "https://example.com/bar" }
+        "#,
+        )
+        .build();
+    let virt = either_manifest(&p);
+    assert!(virt.as_real().is_none());
+    assert!(virt.as_virtual().is_some());
+    match *virt.workspace_config() {
+        WorkspaceConfig::Root(ref root) => {
+            assert_eq!(root.members(), Some(&["a".to_string()][..]))
+        }
+        WorkspaceConfig::Member { .. } => panic!("expected a workspace root"),
+    }
+    assert!(virt.replace().is_empty());
+    assert_eq!(virt.patch().len(), 1);
+    assert_eq!(virt.profiles().base_profile(false).name, "dev");
+    assert!(virt.warnings().warnings()[0].message.contains("bogus"));
+}
+
+#[test]
+fn im_a_teapot() {
+    enable_nightly_features();
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["test-dummy-unstable"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            im-a-teapot = true
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    assert_eq!(manifest(&p).im_a_teapot(), Some(true));
+
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    assert_eq!(manifest(&p).im_a_teapot(), None);
+}
+
+#[test]
+fn dependency_override_diff() {
+    // `[replace]` and `[patch]` can't be used together, so each fixture only
+    // overrides dependencies one way.
+    let package = |dir: &str, overrides: &str| {
+        let p = project(dir)
+            .file(
+                "Cargo.toml",
+                &format!(
+                    r#"
+                    [package]
+                    name = "foo"
+                    version = "0.1.0"
+
+                    {}
+                "#,
+                    overrides
+                ),
+            )
+            .file("src/lib.rs", "")
+            .build();
+        manifest(&p)
+    };
+    let plain = package("plain", "");
+    let replaced = package(
+        "replaced",
+        r#"
+        [replace]
+        "bar:1.0.0" = { git = "https://example.com/bar" }
+        "baz:1.0.0" = { git = "https://example.com/baz" }
+        "#,
+    );
+    let replaced_again = package(
+        "replaced-again",
+        r#"
+        [replace]
+        "bar:1.0.0" = { git = "https://example.com/bar", branch = "fix" }
+        "#,
+    );
+    let patched = package(
+        "patched",
+        r#"
+        [patch.crates-io]
+        qux = { git = "https://example.com/qux" }
+        "#,
+    );
+    let patched_again = package(
+        "patched-again",
+        r#"
+        [patch.crates-io]
+        qux = { git = "https://example.com/qux", branch = "fix" }
+        "#,
+    );
+    assert!(replaced.dependency_override_diff(&replaced).is_empty());
+    assert!(patched.dependency_override_diff(&patched).is_empty());
+
+    let crates_io = "https://github.com/rust-lang/crates.io-index".to_string();
+    let diff = replaced.dependency_override_diff(&replaced_again);
+    assert!(diff.patch.is_empty());
+    assert_eq!(
+        diff.replace,
+        EntryDiff {
+            added: vec![],
+            removed: vec![format!("{}#baz:1.0.0", crates_io)],
+            changed: vec![format!("{}#bar:1.0.0", crates_io)],
+        }
+    );
+
+    let diff = plain.dependency_override_diff(&patched);
+    assert!(diff.replace.is_empty());
+    assert_eq!(
+        diff.patch,
+        EntryDiff {
+            added: vec![crates_io.clone()],
+            removed: vec![],
+            changed: vec![],
+        }
+    );
+
+    let diff = patched.dependency_override_diff(&patched_again);
+    assert_eq!(
+        diff.patch,
+        EntryDiff {
+            added: vec![],
+            removed: vec![],
+            changed: vec![crates_io],
+        }
+    );
+}
@@ -999,6 +999,76 @@ version = "1.0"
     );
 }
 
+#[test]
+fn generated_manifest_inherits_workspace_package() {
+    let p = project("ws")
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["workspace-inheritance"]
+
+            [workspace]
+            members = ["a"]
+
+            [workspace.package]
+            authors = ["ws-author"]
+            license = "MIT"
+        "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+            [package]
+            name = "a"
+            version = "0.1.0"
+        "#,
+        )
+        .file("a/src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("package")
+            .cwd(p.root().join("a"))
+            .masquerade_as_nightly_cargo()
+            .arg("--no-verify"),
+        execs().with_status(0),
+    );
+
+    let f = File::open(&p.root().join("target/package/a-0.1.0.crate")).unwrap();
+    let mut rdr = GzDecoder::new(f);
+    let mut contents = Vec::new();
+    rdr.read_to_end(&mut contents).unwrap();
+    let mut ar = Archive::new(&contents[..]);
+    let mut entry = ar.entries()
+        .unwrap()
+        .map(|f| f.unwrap())
+        .find(|e| e.path().unwrap().ends_with("Cargo.toml"))
+        .unwrap();
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents).unwrap();
+    assert_eq!(
+        &contents[..],
+        r#"# THIS FILE IS AUTOMATICALLY GENERATED BY CARGO
+#
+# When uploading crates to the registry Cargo will automatically
+# "normalize" Cargo.toml files for maximal compatibility
+# with all versions of Cargo and also rewrite `path` dependencies
+# to registry (e.g. crates.io) dependencies
+#
+# If you believe there's an error in this file please file an
+# issue against the rust-lang/cargo repository. If you're
+# editing this file be aware that the upstream Cargo.toml
+# will likely look very different (and much more reasonable)
+
+[package]
+name = "a"
+version = "0.1.0"
+authors = ["ws-author"]
+license = "MIT"
+"#
+    );
+}
+
 #[test]
 fn ignore_workspace_specifier() {
     let p = project("foo")
@@ -413,6 +413,44 @@ fn bogus_default_run() {
     );
 }
 
+#[test]
+fn default_run_feature_candidates() {
+    let p = project("foo")
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["default-run"]
+
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            default-run = [
+                { bin = "b", required-features = ["fancy"] },
+                { bin = "a" },
+            ]
+
+            [features]
+            fancy = []
+        "#,
+        )
+        .file("src/bin/a.rs", r#"fn main() { println!("hello A"); }"#)
+        .file("src/bin/b.rs", r#"fn main() { println!("hello B"); }"#)
+        .build();
+
+    assert_that(
+        p.cargo("run").masquerade_as_nightly_cargo(),
+        execs().with_status(0).with_stdout("hello A"),
+    );
+    assert_that(
+        p.cargo("run")
+            .masquerade_as_nightly_cargo()
+            .arg("--features")
+            .arg("fancy"),
+        execs().with_status(0).with_stdout("hello B"),
+    );
+}
+
 #[test]
 fn default_run_unstable() {
     let p = project("foo")
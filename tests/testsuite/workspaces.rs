@@ -2,7 +2,7 @@ use std::env;
 use std::fs::{self, File};
 use std::io::{Read, Write};
 
-use cargotest::sleep_ms;
+use cargotest::{sleep_ms, ChannelChanger};
 use cargotest::support::{basic_lib_manifest, execs, git, project};
 use cargotest::support::registry::Package;
 use hamcrest::{assert_that, existing_dir, existing_file, is_not};
@@ -2368,3 +2368,88 @@ fn ws_rustc_err() {
             .with_stderr("[ERROR] [..]against an actual package[..]"),
     );
 }
+
+#[test]
+fn workspace_package_inherited() {
+    let p = project("ws")
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["workspace-inheritance"]
+
+            [workspace]
+            members = ["a", "b"]
+
+            [workspace.package]
+            authors = ["ws-author"]
+            license = "MIT"
+        "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+            [package]
+            name = "a"
+            version = "0.1.0"
+        "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+            [package]
+            name = "b"
+            version = "0.1.0"
+            authors = ["b-author"]
+            license-file = "LICENSE"
+        "#,
+        )
+        .file("b/src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("metadata --no-deps").masquerade_as_nightly_cargo(),
+        execs()
+            .with_status(0)
+            .with_stdout_contains(
+                "[..]\"name\":\"a\"[..]\"license\":\"MIT\",\"license_file\":null,\
+                 [..]\"authors\":[\"ws-author\"][..]\"name\":\"b\"[..]",
+            )
+            .with_stdout_contains(
+                "[..]\"name\":\"b\"[..]\"license\":null,\"license_file\":\"LICENSE\",\
+                 [..]\"authors\":[\"b-author\"][..]",
+            ),
+    );
+}
+
+#[test]
+fn workspace_package_requires_feature() {
+    let p = project("ws")
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["a"]
+
+            [workspace.package]
+            authors = ["ws-author"]
+        "#,
+        )
+        .file("a/Cargo.toml", &basic_lib_manifest("a"))
+        .file("a/src/lib.rs", "")
+        .build();
+
+    assert_that(
+        p.cargo("build").masquerade_as_nightly_cargo(),
+        execs().with_status(101).with_stderr(
+            "\
+error: failed to parse manifest at `[..]`
+
+Caused by:
+  feature `workspace-inheritance` is required
+
+consider adding `cargo-features = [\"workspace-inheritance\"]` to the manifest
+",
+        ),
+    );
+}